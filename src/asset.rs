@@ -1,11 +1,26 @@
-use crate::util::{alpha_bleed::alpha_bleed, svg::svg_to_png};
+use crate::util::{
+    alpha_bleed::alpha_bleed,
+    audio::transcode_to_ogg,
+    color_profile::convert_to_srgb,
+    fbx::validate_fbx,
+    flipbook::{animation_to_sheet, Flipbook, MAX_SHEET_SIZE},
+    optimize_png::{optimize_png, PngOptimization},
+    premultiply::premultiply_alpha,
+    svg::svg_to_png,
+};
 use anyhow::{bail, Context};
 use blake3::Hasher;
-use image::{DynamicImage, ImageFormat};
+use image::{imageops::FilterType, DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use log::info;
 use rbx_xml::DecodeOptions;
 use rbxcloud::rbx::v1::assets::AssetType as CloudAssetType;
 use resvg::usvg::fontdb::Database;
-use std::{io::Cursor, sync::Arc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Cursor,
+    sync::Arc,
+};
 
 pub enum AudioKind {
     Mp3,
@@ -30,6 +45,219 @@ pub enum AssetKind {
     Model(ModelKind),
 }
 
+impl AssetKind {
+    pub fn asset_type(&self) -> AssetType {
+        match self {
+            AssetKind::Decal(_) => AssetType::Decal,
+            AssetKind::Audio(_) => AssetType::Audio,
+            AssetKind::Model(ModelKind::Model) => AssetType::Model,
+            AssetKind::Model(ModelKind::Animation) => AssetType::Animation,
+        }
+    }
+}
+
+/// The type an asset is uploaded as, independent of its file format.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetType {
+    Decal,
+    Audio,
+    Model,
+    Animation,
+}
+
+impl AssetType {
+    /// The type files with the given extension are uploaded as, unless
+    /// overridden.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "mp3" | "ogg" | "wav" | "flac" => Some(AssetType::Audio),
+            "png" | "jpg" | "bmp" | "tga" | "svg" | "webp" | "gif" | "apng" => {
+                Some(AssetType::Decal)
+            }
+            "fbx" => Some(AssetType::Model),
+            "rbxm" | "rbxmx" => Some(AssetType::Animation),
+            _ => None,
+        }
+    }
+}
+
+/// How to upload a file in a format Roblox doesn't accept, if there's a way.
+pub fn unsupported_format_hint(ext: &str) -> Option<&'static str> {
+    match ext {
+        "obj" | "stl" | "gltf" | "glb" | "dae" | "3ds" | "blend" => {
+            Some("Roblox only accepts meshes as .fbx, so export it as FBX instead")
+        }
+        "avif" => Some("AVIF images can't be converted, so export it as PNG or WebP instead"),
+        "ttf" | "otf" | "woff" | "woff2" => {
+            Some("Roblox doesn't accept uploaded fonts, so use one from its font library with Font.fromName or Font.fromId instead")
+        }
+        _ => None,
+    }
+}
+
+impl Display for AssetType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetType::Decal => write!(f, "Decal"),
+            AssetType::Audio => write!(f, "Audio"),
+            AssetType::Model => write!(f, "Model"),
+            AssetType::Animation => write!(f, "Animation"),
+        }
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// The IEND chunk every PNG ends with, including its length and CRC.
+const PNG_END: &[u8] = b"\0\0\0\0IEND\xae\x42\x60\x82";
+
+/// Checks that a file could be the format its extension says, to catch
+/// empty, truncated and mislabeled files before they fail to decode deep
+/// inside processing. Returns what is wrong with it, if anything.
+pub fn find_content_issue(data: &[u8], ext: &str) -> Option<&'static str> {
+    if data.is_empty() {
+        return Some("it is empty");
+    }
+
+    let valid = match ext {
+        "png" | "apng" => {
+            if !data.starts_with(PNG_SIGNATURE) {
+                return Some("it does not have a PNG signature");
+            }
+            // Some editors write data after the end chunk, which decoders
+            // ignore, so it only has to be somewhere.
+            if !data.windows(PNG_END.len()).any(|window| window == PNG_END) {
+                return Some("it is truncated, with no PNG end chunk");
+            }
+            true
+        }
+        "jpg" => data.starts_with(&[0xff, 0xd8, 0xff]),
+        "bmp" => data.starts_with(b"BM"),
+        "gif" => data.starts_with(b"GIF8"),
+        "webp" => data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP"),
+        "ogg" => data.starts_with(b"OggS"),
+        "wav" => data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WAVE"),
+        "flac" => data.starts_with(b"fLaC"),
+        "mp3" => is_mp3(data),
+        "rbxm" => data.starts_with(b"<roblox!"),
+        "rbxmx" => data.starts_with(b"<roblox"),
+        // TGA has no signature, and SVG and FBX may start with any text.
+        _ => true,
+    };
+
+    (!valid).then_some("its contents do not match its extension")
+}
+
+/// MP3s start with an ID3 or APE tag, or a frame sync, which some encoders
+/// pad with zeros.
+fn is_mp3(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(data.len());
+    let data = &data[start..];
+
+    data.starts_with(b"ID3")
+        || data.starts_with(b"APETAGEX")
+        || matches!(data, [0xff, second, ..] if second & 0xe0 == 0xe0)
+}
+
+/// How an asset's processed output differs from its source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessingDelta {
+    pub source_extension: String,
+    pub extension: String,
+    pub source_size: usize,
+    pub size: usize,
+    pub source_dimensions: Option<(u32, u32)>,
+    pub dimensions: Option<(u32, u32)>,
+    /// The colour profile the image was converted to sRGB from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_profile: Option<String>,
+}
+
+impl ProcessingDelta {
+    pub fn is_changed(&self) -> bool {
+        self.source_extension != self.extension
+            || self.source_size != self.size
+            || self.source_dimensions != self.dimensions
+            || self.color_profile.is_some()
+    }
+}
+
+/// The filter images are resampled with when they are downscaled.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+impl From<ResizeFilter> for FilterType {
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::CatmullRom => FilterType::CatmullRom,
+            ResizeFilter::Gaussian => FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// The format audio Roblox doesn't accept is converted to.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioTranscode {
+    Ogg,
+}
+
+/// How assets are changed before they are uploaded.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessOptions {
+    /// How many times their intrinsic size SVGs are rendered at.
+    pub svg_scale: f32,
+    /// Whether transparent pixels of images are bled into.
+    pub alpha_bleed: bool,
+    /// Whether the colours of images are multiplied by their alpha.
+    pub premultiply_alpha: bool,
+    /// Whether BMP and TGA images are uploaded as PNGs.
+    pub convert_to_png: bool,
+    /// Whether images with a colour profile are converted to sRGB.
+    pub convert_color_profiles: bool,
+    /// How much images are scaled by, for their resolution variants.
+    pub image_scale: f32,
+    /// The largest width or height images are uploaded at.
+    pub max_image_size: Option<u32>,
+    pub resize_filter: ResizeFilter,
+    /// What WAV and FLAC files are converted to, as they can't be uploaded
+    /// as they are.
+    pub transcode_audio: Option<AudioTranscode>,
+    /// The level in decibels below which silence is trimmed from the ends of
+    /// transcoded audio.
+    pub trim_silence: Option<f32>,
+}
+
+impl Default for ProcessOptions {
+    fn default() -> Self {
+        Self {
+            svg_scale: 1.0,
+            alpha_bleed: true,
+            premultiply_alpha: false,
+            convert_to_png: false,
+            convert_color_profiles: false,
+            image_scale: 1.0,
+            max_image_size: None,
+            resize_filter: ResizeFilter::Lanczos3,
+            transcode_audio: None,
+            trim_silence: None,
+        }
+    }
+}
+
 pub struct Asset {
     name: String,
     ext: String,
@@ -37,6 +265,15 @@ pub struct Asset {
 
     kind: AssetKind,
     cloud_type: Option<CloudAssetType>,
+    processing: ProcessingDelta,
+    flipbook: Option<Flipbook>,
+    /// The hash of the file a transcoded asset was made from, which is used
+    /// instead of its own, as encoders may not give the same output for the
+    /// same input between versions.
+    source_hash: Option<String>,
+    /// Problems that didn't stop the asset from being processed, which the
+    /// sync reports, or fails on in strict mode.
+    warnings: Vec<String>,
 }
 
 enum ModelFileFormat {
@@ -68,6 +305,40 @@ fn verify_animation(data: Vec<u8>, format: ModelFileFormat) -> anyhow::Result<Ve
     Ok(writer.into_inner())
 }
 
+/// Hashes an image's source file with the options that change how it is
+/// processed, so it is uploaded again when either changes.
+fn image_source_hash(data: &[u8], options: &ProcessOptions) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(data);
+    hasher.update(&[
+        options.alpha_bleed as u8,
+        options.premultiply_alpha as u8,
+        options.convert_color_profiles as u8,
+        options.resize_filter as u8,
+    ]);
+    hasher.update(&options.image_scale.to_le_bytes());
+    hasher.update(&options.max_image_size.unwrap_or_default().to_le_bytes());
+    hasher.finalize().to_string()
+}
+
+fn hash_data(data: &[u8]) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(data);
+    hasher.finalize().to_string()
+}
+
+fn webp_to_png(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let image = image::load_from_memory_with_format(data, ImageFormat::WebP)
+        .context("Failed to decode WebP file")?;
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .context("Failed to encode PNG")?;
+
+    Ok(png.into_inner())
+}
+
 pub struct UploadResult {
     pub asset_id: u64,
     pub csrf: Option<String>,
@@ -79,20 +350,88 @@ impl Asset {
         mut data: Vec<u8>,
         mut ext: &str,
         font_db: Arc<Database>,
+        type_override: Option<AssetType>,
+        options: &ProcessOptions,
     ) -> anyhow::Result<Self> {
+        let source_extension = ext.to_string();
+        let source_size = data.len();
+        let mut source_dimensions = None;
+        let mut dimensions = None;
+        let mut flipbook = None;
+        let mut source_hash = None;
+        let mut color_profile = None;
+        let mut warnings = Vec::new();
+
         let kind = match ext {
             "mp3" => AssetKind::Audio(AudioKind::Mp3),
             "ogg" => AssetKind::Audio(AudioKind::Ogg),
+            "wav" | "flac" => match options.transcode_audio {
+                Some(AudioTranscode::Ogg) => {
+                    // Trimming changes the output, so it has to change the
+                    // hash for the sound to be uploaded again.
+                    let mut hasher = Hasher::new();
+                    hasher.update(&data);
+                    if let Some(threshold) = options.trim_silence {
+                        hasher.update(&threshold.to_le_bytes());
+                    }
+                    source_hash = Some(hasher.finalize().to_string());
+                    data = transcode_to_ogg(&data, options.trim_silence)
+                        .with_context(|| format!("Failed to transcode {name} to Ogg"))?;
+                    ext = "ogg";
+                    AssetKind::Audio(AudioKind::Ogg)
+                }
+                None => bail!(
+                    "Roblox doesn't accept .{ext} files. Set transcode_audio to \"ogg\" to convert them"
+                ),
+            },
             "png" => AssetKind::Decal(DecalKind::Png),
             "jpg" => AssetKind::Decal(DecalKind::Jpg),
+            // Re-encoded below when the image is decoded.
+            "bmp" | "tga" if options.convert_to_png => {
+                ext = "png";
+                AssetKind::Decal(DecalKind::Png)
+            }
             "bmp" => AssetKind::Decal(DecalKind::Bmp),
             "tga" => AssetKind::Decal(DecalKind::Tga),
             "svg" => {
-                data = svg_to_png(&data, font_db).await?;
+                data = svg_to_png(&data, font_db, options.svg_scale).await?;
                 ext = "png";
                 AssetKind::Decal(DecalKind::Png)
             }
-            "fbx" => AssetKind::Model(ModelKind::Model),
+            // Roblox doesn't accept WebP, so it is uploaded as a PNG. Its hash
+            // comes from the WebP, so a change in how PNGs are encoded doesn't
+            // upload it again.
+            "webp" => {
+                source_hash = Some(image_source_hash(&data, options));
+                data = webp_to_png(&data)?;
+                ext = "png";
+                AssetKind::Decal(DecalKind::Png)
+            }
+            "gif" | "apng" => {
+                let (sheet, layout) = animation_to_sheet(&data, ext)?;
+                let (width, height) = layout.sheet_size();
+                if width > MAX_SHEET_SIZE || height > MAX_SHEET_SIZE {
+                    warnings.push(format!(
+                        "{name} has {} frames of {}x{}, which make a {width}x{height} sheet. Roblox downscales images larger than {MAX_SHEET_SIZE}x{MAX_SHEET_SIZE}, blurring every frame, so use fewer or smaller frames",
+                        layout.durations.len(),
+                        layout.frame_width,
+                        layout.frame_height
+                    ));
+                }
+                data = sheet;
+                flipbook = Some(layout);
+                ext = "png";
+                AssetKind::Decal(DecalKind::Png)
+            }
+            "fbx" => {
+                if let Some(warning) = validate_fbx(&name, &data)? {
+                    warnings.push(warning);
+                }
+                AssetKind::Model(ModelKind::Model)
+            }
+            "rbxm" | "rbxmx" if type_override == Some(AssetType::Model) => {
+                AssetKind::Model(ModelKind::Model)
+            }
             "rbxm" | "rbxmx" => {
                 let format = if ext == "rbxm" {
                     ModelFileFormat::Binary
@@ -107,6 +446,12 @@ impl Asset {
             _ => bail!("Unknown extension .{ext}"),
         };
 
+        if let Some(type_override) = type_override {
+            if kind.asset_type() != type_override {
+                bail!("{name} cannot be uploaded as {type_override}");
+            }
+        }
+
         let cloud_type = match &kind {
             AssetKind::Decal(kind) => match kind {
                 DecalKind::Png => Some(CloudAssetType::DecalPng),
@@ -119,14 +464,79 @@ impl Asset {
                 AudioKind::Ogg => Some(CloudAssetType::AudioOgg),
             },
             AssetKind::Model(kind) => match kind {
-                ModelKind::Model => Some(CloudAssetType::ModelFbx),
+                ModelKind::Model if ext == "fbx" => Some(CloudAssetType::ModelFbx),
+                ModelKind::Model => None,
                 ModelKind::Animation => None,
             },
         };
 
         if let AssetKind::Decal(_) = &kind {
-            let mut image: DynamicImage = image::load_from_memory(&data)?;
-            alpha_bleed(&mut image);
+            let mut reader = ImageReader::new(Cursor::new(&data)).with_guessed_format()?;
+            // TGA files have no signature to guess their format from.
+            if reader.format().is_none() {
+                if let Some(format) = ImageFormat::from_extension(&source_extension) {
+                    reader.set_format(format);
+                }
+            }
+            let mut decoder = reader.into_decoder()?;
+            let icc = decoder.icc_profile()?;
+            let mut image = DynamicImage::from_decoder(decoder)?;
+            if source_extension != "svg" {
+                source_dimensions = Some((image.width(), image.height()));
+            }
+
+            if let (true, Some(icc)) = (options.convert_color_profiles, icc) {
+                match convert_to_srgb(&mut image, &icc) {
+                    Ok(profile) => color_profile = profile,
+                    Err(e) => warnings.push(format!(
+                        "Uploading {name} without converting it to sRGB: {e:#}"
+                    )),
+                }
+            }
+
+            // Flipbooks aren't resized, as their frames would no longer line
+            // up with their layout.
+            if options.image_scale < 1.0 && flipbook.is_none() {
+                let scaled =
+                    |size: u32| ((size as f32 * options.image_scale).round() as u32).max(1);
+
+                // Bleeding first keeps transparent pixels from darkening the
+                // edges they are averaged into.
+                if options.alpha_bleed {
+                    alpha_bleed(&mut image);
+                }
+                image = image.resize_exact(
+                    scaled(image.width()),
+                    scaled(image.height()),
+                    options.resize_filter.into(),
+                );
+            }
+
+            let too_large = options
+                .max_image_size
+                .filter(|max| image.width() > *max || image.height() > *max);
+            if let (Some(max), None) = (too_large, &flipbook) {
+                if options.alpha_bleed {
+                    alpha_bleed(&mut image);
+                }
+                let resized = image.resize(max, max, options.resize_filter.into());
+                info!(
+                    "Downscaled {name} from {}x{} to {}x{}",
+                    image.width(),
+                    image.height(),
+                    resized.width(),
+                    resized.height()
+                );
+                image = resized;
+            }
+
+            if options.alpha_bleed {
+                alpha_bleed(&mut image);
+            }
+            if options.premultiply_alpha {
+                premultiply_alpha(&mut image);
+            }
+            dimensions = Some((image.width(), image.height()));
 
             let format = ImageFormat::from_extension(ext)
                 .context("Failed to get image format from extension")?;
@@ -137,19 +547,52 @@ impl Asset {
             data = new_data.into_inner();
         }
 
+        let processing = ProcessingDelta {
+            source_extension,
+            extension: ext.to_string(),
+            source_size,
+            size: data.len(),
+            source_dimensions,
+            dimensions,
+            color_profile,
+        };
+
         Ok(Self {
             name,
             ext: ext.to_string(),
             data,
             kind,
             cloud_type,
+            processing,
+            flipbook,
+            source_hash,
+            warnings,
         })
     }
 
+    /// Shrinks the asset before it is uploaded, if it is uploaded as a PNG.
+    pub fn optimize(&mut self, optimization: PngOptimization) -> anyhow::Result<()> {
+        if self.ext != "png" {
+            return Ok(());
+        }
+
+        self.data = optimize_png(std::mem::take(&mut self.data), optimization)?;
+        self.processing.size = self.data.len();
+        if let Some(hash) = &mut self.source_hash {
+            let mut hasher = Hasher::new();
+            hasher.update(hash.as_bytes());
+            hasher.update(&[optimization as u8]);
+            *hash = hasher.finalize().to_string();
+        }
+
+        Ok(())
+    }
+
     pub fn hash(&self) -> String {
-        let mut hasher = Hasher::new();
-        hasher.update(&self.data);
-        hasher.finalize().to_string()
+        match &self.source_hash {
+            Some(hash) => hash.clone(),
+            None => hash_data(&self.data),
+        }
     }
 
     pub fn name(&self) -> &str {
@@ -171,4 +614,159 @@ impl Asset {
     pub fn cloud_type(&self) -> Option<CloudAssetType> {
         self.cloud_type
     }
+
+    pub fn processing(&self) -> &ProcessingDelta {
+        &self.processing
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Where each frame is, if the asset was an animated image.
+    pub fn flipbook(&self) -> Option<&Flipbook> {
+        self.flipbook.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{color_profile::tests::tagged_png, flipbook::tests::gif};
+    use moxcms::ColorProfile;
+
+    #[test]
+    fn flags_empty_files() {
+        assert_eq!(find_content_issue(b"", "png"), Some("it is empty"));
+        assert_eq!(find_content_issue(b"", "fbx"), Some("it is empty"));
+    }
+
+    #[test]
+    fn flags_truncated_pngs() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(b"\0\0\0\x0dIHDR");
+        assert_eq!(
+            find_content_issue(&png, "png"),
+            Some("it is truncated, with no PNG end chunk")
+        );
+
+        png.extend_from_slice(PNG_END);
+        assert_eq!(find_content_issue(&png, "png"), None);
+        // Data after the end chunk is ignored by decoders.
+        png.extend_from_slice(b"trailing");
+        assert_eq!(find_content_issue(&png, "png"), None);
+    }
+
+    #[test]
+    fn flags_mislabeled_files() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(PNG_END);
+        assert_eq!(
+            find_content_issue(&png, "jpg"),
+            Some("its contents do not match its extension")
+        );
+        assert_eq!(find_content_issue(b"\xff\xd8\xff\xe0", "jpg"), None);
+        assert_eq!(
+            find_content_issue(b"\xff\xd8\xff\xe0", "png"),
+            Some("it does not have a PNG signature")
+        );
+    }
+
+    #[test]
+    fn tells_riff_formats_apart() {
+        let wav = b"RIFF\x24\0\0\0WAVEfmt ";
+        let webp = b"RIFF\x24\0\0\0WEBPVP8 ";
+        assert_eq!(find_content_issue(wav, "wav"), None);
+        assert_eq!(find_content_issue(webp, "webp"), None);
+        assert!(find_content_issue(webp, "wav").is_some());
+        assert!(find_content_issue(wav, "webp").is_some());
+        assert!(find_content_issue(b"RIFF", "wav").is_some());
+    }
+
+    #[test]
+    fn accepts_padded_and_tagged_mp3s() {
+        assert_eq!(find_content_issue(b"ID3\x04\0", "mp3"), None);
+        assert_eq!(find_content_issue(b"\xff\xfb\x90\x64", "mp3"), None);
+        assert_eq!(find_content_issue(b"\0\0\0\0\xff\xfb\x90\x64", "mp3"), None);
+        assert_eq!(find_content_issue(b"APETAGEX\xd0\x07", "mp3"), None);
+
+        assert!(find_content_issue(b"\0\0\0\0", "mp3").is_some());
+        assert!(find_content_issue(b"\xff\x1b", "mp3").is_some());
+        assert!(find_content_issue(b"OggS", "mp3").is_some());
+    }
+
+    async fn tagged_asset(data: Vec<u8>) -> Asset {
+        let options = ProcessOptions {
+            convert_color_profiles: true,
+            ..Default::default()
+        };
+
+        Asset::new(
+            "tagged.png".to_string(),
+            data,
+            "png",
+            Arc::new(Database::new()),
+            None,
+            &options,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn uploads_tagged_images_as_srgb() {
+        let data = tagged_png(
+            [200, 100, 50],
+            &ColorProfile::new_display_p3().encode().unwrap(),
+        );
+        let asset = tagged_asset(data).await;
+        assert!(asset.warnings().is_empty());
+        assert_eq!(
+            asset.processing().color_profile.as_deref(),
+            Some("Display P3")
+        );
+
+        let mut decoder = ImageReader::new(Cursor::new(asset.data()))
+            .with_guessed_format()
+            .unwrap()
+            .into_decoder()
+            .unwrap();
+        assert_eq!(decoder.icc_profile().unwrap(), None);
+
+        let image = DynamicImage::from_decoder(decoder).unwrap();
+        let pixel = image.to_rgb8().get_pixel(0, 0).0;
+        for (channel, expected) in pixel.iter().zip([215u8, 93, 31]) {
+            assert!(channel.abs_diff(expected) <= 1, "{pixel:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn warns_about_unreadable_profiles() {
+        let asset = tagged_asset(tagged_png([200, 100, 50], b"not a profile")).await;
+
+        assert_eq!(asset.processing().color_profile, None);
+        assert_eq!(asset.warnings().len(), 1);
+        assert!(
+            asset.warnings()[0].starts_with("Uploading tagged.png without converting it to sRGB")
+        );
+    }
+
+    #[tokio::test]
+    async fn warns_about_oversized_flipbooks() {
+        let frames = [(1050, 1, [255, 0, 0, 255], 100); 2];
+        let asset = Asset::new(
+            "wide.gif".to_string(),
+            gif(&frames),
+            "gif",
+            Arc::new(Database::new()),
+            None,
+            &ProcessOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(asset.flipbook().unwrap().sheet_size(), (1050, 2));
+        assert_eq!(asset.warnings().len(), 1);
+        assert!(asset.warnings()[0].starts_with("wide.gif has 2 frames of 1050x1"));
+    }
 }
@@ -1,5 +1,6 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_verbosity_flag::{InfoLevel, Verbosity};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(version, about = "Upload and reference Roblox assets in code.")]
@@ -9,6 +10,10 @@ pub struct Cli {
 
     #[command(flatten)]
     pub verbose: Verbosity<InfoLevel>,
+
+    /// Fail immediately on anything that would contact Roblox.
+    #[arg(long, global = true)]
+    pub offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -16,14 +21,29 @@ pub enum Commands {
     /// Sync assets.
     Sync(SyncArgs),
 
+    /// Regenerate code from the lockfile without syncing.
+    Codegen(CodegenArgs),
+
+    /// Check that generated code matches the lockfile.
+    Verify(VerifyArgs),
+
     /// List assets in the lockfile.
     List,
 
     /// Initialize a new configuration.
     Init,
 
+    /// Create a new project with a config, sample assets and a CI workflow.
+    New(crate::commands::new::NewArgs),
+
     /// Migrate an existing tarmac-manifest.toml to a lockfile.
     MigrateTarmacManifest(crate::commands::migrate_tarmac_manifest::MigrateTarmacManifestArgs),
+
+    /// Create or restore snapshots of the lockfile and generated code.
+    Snapshot(crate::commands::snapshot::SnapshotArgs),
+
+    /// Export information about synced assets for other tools.
+    Export(crate::commands::export::ExportArgs),
 }
 
 #[derive(ValueEnum, Clone)]
@@ -52,4 +72,72 @@ pub struct SyncArgs {
     /// Skip asset syncing and only display what assets will be synced.
     #[arg(long, action)]
     pub dry_run: bool,
+
+    /// The most Robux this sync may spend on paid uploads.
+    /// Without it, you will be asked to confirm any paid uploads.
+    #[arg(long)]
+    pub budget: Option<u64>,
+
+    /// Refuse to sync to the cloud if assets have uncommitted changes.
+    #[arg(long, action)]
+    pub require_clean_git: bool,
+
+    /// Fail on anything that would otherwise only be a warning, such as files
+    /// that could not be processed.
+    #[arg(long, action)]
+    pub strict: bool,
+
+    /// Only check that the generated code matches the lockfile, failing if
+    /// it is out of date. Nothing is synced.
+    #[arg(long, action)]
+    pub check_codegen: bool,
+
+    /// Print the path of every file this sync changed, one per line.
+    #[arg(long, action)]
+    pub print_changed: bool,
+
+    /// Write a trace of the sync to this path, which can be opened in
+    /// Perfetto to see where time is spent.
+    #[arg(long)]
+    pub trace: Option<PathBuf>,
+
+    /// Write the tree of keys in the generated code to this path as JSON,
+    /// with what each asset resolved to, for use by other code generators.
+    #[arg(long)]
+    pub emit_node_tree: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum CodegenLang {
+    Luau,
+    Ts,
+}
+
+#[derive(Args)]
+pub struct CodegenArgs {
+    /// Only generate code for this language.
+    #[arg(long)]
+    pub lang: Option<CodegenLang>,
+
+    /// Generate code from this lockfile instead of asphalt.lock.toml, such as
+    /// one from an older release.
+    #[arg(long)]
+    pub lockfile: Option<PathBuf>,
+
+    /// A TOML file mapping asset paths to the keys they are generated with.
+    /// Assets without a key are left out. Required without an asphalt.toml.
+    #[arg(long)]
+    pub keys: Option<PathBuf>,
+
+    /// Write generated code to this directory instead of `write_dir`.
+    #[arg(long)]
+    pub write_dir: Option<String>,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Also process every asset again to check that the lockfile's hashes
+    /// match the files on disk.
+    #[arg(long, action)]
+    pub local: bool,
 }
@@ -0,0 +1,123 @@
+use crate::LockFile;
+use anyhow::{bail, Context};
+use log::info;
+use serde::Serialize;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    env,
+    path::{Path, PathBuf},
+};
+use tokio::fs::write;
+
+/// Mixed into the signing key, so a key used elsewhere can't produce a valid
+/// fingerprint signature.
+const SIGNING_CONTEXT: &str = "asphalt 2024-11 fingerprint signature";
+
+#[derive(Debug, Serialize)]
+struct Fingerprint<'a> {
+    path: &'a str,
+    hash: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct FingerprintManifest<'a> {
+    /// The content hash of every asset, keyed by its ID.
+    assets: BTreeMap<String, Fingerprint<'a>>,
+    /// The BLAKE3 hash of `assets` as compact JSON.
+    checksum: String,
+    /// The keyed BLAKE3 hash of `assets` as compact JSON, if a key was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+fn get_sign_key(arg_key: Option<String>) -> Option<String> {
+    arg_key.or_else(|| env::var("ASPHALT_SIGN_KEY").ok())
+}
+
+async fn export_fingerprints(
+    lockfile: &LockFile,
+    output: &Path,
+    sign_key: Option<String>,
+) -> anyhow::Result<()> {
+    let mut assets = BTreeMap::new();
+    for (path, entry) in &lockfile.entries {
+        match assets.entry(entry.asset_id.to_string()) {
+            Entry::Occupied(existing) => {
+                let existing: &Fingerprint = existing.get();
+                bail!(
+                    "{} and {path} have the same asset ID {}, so they can't both be fingerprinted",
+                    existing.path,
+                    entry.asset_id
+                );
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(Fingerprint {
+                    path,
+                    hash: &entry.hash,
+                });
+            }
+        }
+    }
+
+    let signed = serde_json::to_string(&assets)?;
+    let signature = get_sign_key(sign_key).map(|key| {
+        let key = blake3::derive_key(SIGNING_CONTEXT, key.as_bytes());
+        blake3::keyed_hash(&key, signed.as_bytes())
+            .to_hex()
+            .to_string()
+    });
+
+    let manifest = FingerprintManifest {
+        checksum: blake3::hash(signed.as_bytes()).to_hex().to_string(),
+        signature,
+        assets,
+    };
+
+    write(output, serde_json::to_string_pretty(&manifest)?)
+        .await
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    info!(
+        "Exported fingerprints of {} asset{} to {}{}",
+        manifest.assets.len(),
+        if manifest.assets.len() == 1 { "" } else { "s" },
+        output.display(),
+        if manifest.signature.is_some() {
+            ", signed"
+        } else {
+            ""
+        }
+    );
+
+    Ok(())
+}
+
+pub async fn export(args: ExportArgs, lockfile: LockFile) -> anyhow::Result<()> {
+    match args.command {
+        ExportCommand::Fingerprints { output, sign_key } => {
+            export_fingerprints(&lockfile, &output, sign_key).await
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    pub command: ExportCommand,
+}
+
+#[derive(clap::Subcommand)]
+pub enum ExportCommand {
+    /// Write the content hash of every synced asset, keyed by asset ID, for
+    /// tools that check assets haven't been swapped.
+    Fingerprints {
+        /// Where to write the fingerprints to.
+        #[arg(short, long, default_value = "fingerprints.json")]
+        output: PathBuf,
+
+        /// A secret to sign the fingerprints with. Falls back to the
+        /// ASPHALT_SIGN_KEY environment variable.
+        #[arg(long)]
+        sign_key: Option<String>,
+    },
+}
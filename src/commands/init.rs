@@ -6,7 +6,7 @@ use inquire::{
     Confirm, CustomType, CustomUserError, Select, Text,
 };
 use log::info;
-use std::{path::Path, process::exit};
+use std::{collections::BTreeMap, path::Path, process::exit};
 
 pub fn dir_validator(str: &str) -> Result<Validation, CustomUserError> {
     let path = Path::new(str);
@@ -58,10 +58,17 @@ pub async fn init() -> anyhow::Result<()> {
         .prompt()
         .unwrap_or_else(|_| exit(1));
 
-    let codegen_style = Select::new("Style", vec![CodegenStyle::Flat, CodegenStyle::Nested])
-        .with_help_message("The style to use for generated code.")
-        .prompt()
-        .unwrap_or_else(|_| exit(1));
+    let codegen_style = Select::new(
+        "Style",
+        vec![
+            CodegenStyle::Flat,
+            CodegenStyle::Nested,
+            CodegenStyle::ByType,
+        ],
+    )
+    .with_help_message("The style to use for generated code.")
+    .prompt()
+    .unwrap_or_else(|_| exit(1));
 
     let strip_extension = Confirm::new("Strip file extensions")
         .with_help_message("Strip file extensions from generated code.")
@@ -72,15 +79,63 @@ pub async fn init() -> anyhow::Result<()> {
     let config: SyncConfig = SyncConfig {
         asset_dir,
         write_dir,
+        ts_write_dir: None,
         exclude_assets: Vec::new(),
+        bleed_exclude: Vec::new(),
+        premultiply_alpha: Vec::new(),
         creator: Creator { creator_type, id },
         codegen: CodegenConfig {
             output_name,
+            luau: None,
             typescript: Some(typescript),
+            typescript_mode: None,
             style: Some(codegen_style),
             strip_extension: Some(strip_extension),
+            luau_types: None,
+            emit_meta: None,
+            json: None,
+            sort: None,
+            split_by_folder: None,
+            content: None,
+            key_style: None,
+            numeric_ids: None,
+            include_dimensions: None,
+            indent_type: None,
+            indent_width: None,
+            trailing_commas: None,
+            header: None,
+            lookup: None,
+            source_comments: None,
+            variant_separator: None,
+            frame_sequences: None,
+            frame_rate: None,
+            rojo_fragment: None,
+            rojo_path: None,
+            init_shim: None,
+            extra_outputs: Vec::new(),
+            bundles: BTreeMap::new(),
+            preload_order: Vec::new(),
+            preload_list: None,
+            preload_types: Vec::new(),
+            slice_centers: BTreeMap::new(),
         },
         existing: None,
+        type_overrides: BTreeMap::new(),
+        upload_prices: BTreeMap::new(),
+        denied_hashes: Vec::new(),
+        locked: Vec::new(),
+        optimize_png: None,
+        svg_scale: None,
+        convert_to_png: None,
+        convert_color_profiles: None,
+        max_image_size: None,
+        resize_filter: None,
+        transcode_audio: None,
+        trim_silence: None,
+        resolution_variants: BTreeMap::new(),
+        report_dir: None,
+        require_clean_git: None,
+        commit_message: None,
     };
 
     config.write().await.context("Failed to write config")?;
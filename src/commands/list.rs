@@ -2,7 +2,10 @@ use crate::LockFile;
 
 pub async fn list(lockfile: LockFile) -> anyhow::Result<()> {
     for (path, entry) in lockfile.entries {
-        println!("\"{}\": {}", path, entry.asset_id);
+        match entry.note {
+            Some(note) => println!("\"{}\": {} # {}", path, entry.asset_id, note),
+            None => println!("\"{}\": {}", path, entry.asset_id),
+        }
     }
 
     Ok(())
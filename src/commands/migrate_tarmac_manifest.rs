@@ -4,7 +4,7 @@ use anyhow::Context;
 use resvg::usvg::fontdb::Database;
 use serde::{Deserialize, Serialize};
 
-use crate::asset::Asset;
+use crate::asset::{Asset, ProcessOptions};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct TarmacManifest {
@@ -51,6 +51,8 @@ pub async fn migrate_manifest(args: MigrateTarmacManifestArgs) -> anyhow::Result
             content,
             &path.extension().unwrap_or_default().to_string_lossy(),
             font_db,
+            None,
+            &ProcessOptions::default(),
         )
         .await
         .with_context(|| format!("Failed to create asset for {}", path.to_string_lossy()))?;
@@ -60,6 +62,7 @@ pub async fn migrate_manifest(args: MigrateTarmacManifestArgs) -> anyhow::Result
             crate::FileEntry {
                 asset_id: entry.id,
                 hash: asset.hash(),
+                note: None,
             },
         );
     }
@@ -1,4 +1,7 @@
+pub mod export;
 pub mod init;
 pub mod list;
 pub mod migrate_tarmac_manifest;
+pub mod new;
+pub mod snapshot;
 pub mod sync;
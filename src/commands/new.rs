@@ -0,0 +1,140 @@
+use anyhow::{bail, Context};
+use clap::Args;
+use console::style;
+use image::{Rgba, RgbaImage};
+use log::info;
+use std::{io::Cursor, path::Path};
+use tokio::fs::{create_dir_all, read_to_string, write};
+
+#[derive(Args)]
+pub struct NewArgs {
+    /// The directory to create the project in.
+    pub dir: String,
+}
+
+static CONFIG: &str = r#"asset_dir = "assets/"
+write_dir = "src/shared/"
+
+[creator]
+type = "user"
+# Replace this with your user ID, or set `type` to "group" and use a group ID.
+id = 0
+
+[codegen]
+style = "flat"
+strip_extension = true
+"#;
+
+static GITIGNORE_ENTRIES: &[&str] = &["# Asphalt", ".env", ".asphalt/", ".asphalt-debug/"];
+
+static WORKFLOW: &str = r#"name: Sync assets
+
+on:
+  push:
+    branches: [main]
+    paths: ["assets/**", "asphalt.toml"]
+
+jobs:
+  sync:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: write
+    steps:
+      - uses: actions/checkout@v4
+
+      - name: Install Asphalt
+        run: cargo install asphalt --locked --features embedded-font
+
+      # Add an Open Cloud API key as the ASPHALT_API_KEY repository secret.
+      - name: Sync assets
+        run: asphalt sync --strict --budget 0
+        env:
+          ASPHALT_API_KEY: ${{ secrets.ASPHALT_API_KEY }}
+
+      - name: Commit lockfile and generated code
+        run: |
+          git config user.name "github-actions[bot]"
+          git config user.email "github-actions[bot]@users.noreply.github.com"
+          git add asphalt.lock.toml src/shared
+          git diff --cached --quiet || (git commit -m "Sync assets" && git push)
+"#;
+
+/// A small square to sync, so the project works before any real assets are
+/// added.
+fn sample_image() -> anyhow::Result<Vec<u8>> {
+    let image = RgbaImage::from_pixel(16, 16, Rgba([255, 255, 255, 255]));
+
+    let mut data = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+        .context("Failed to encode sample image")?;
+
+    Ok(data)
+}
+
+async fn write_new(path: &Path, contents: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    if path.exists() {
+        info!("Skipping {}, as it already exists", path.display());
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    write(path, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Adds Asphalt's entries to a .gitignore, keeping any that are already
+/// there.
+async fn update_gitignore(path: &Path) -> anyhow::Result<()> {
+    let mut contents = read_to_string(path).await.unwrap_or_default();
+
+    let missing: Vec<_> = GITIGNORE_ENTRIES
+        .iter()
+        .filter(|entry| !contents.lines().any(|line| line.trim() == **entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    for entry in missing {
+        contents.push_str(entry);
+        contents.push('\n');
+    }
+
+    write(path, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub async fn new_project(args: NewArgs) -> anyhow::Result<()> {
+    let dir = Path::new(&args.dir);
+
+    if dir.join("asphalt.toml").exists() {
+        bail!("{} already has an Asphalt project", dir.display());
+    }
+
+    write_new(&dir.join("asphalt.toml"), CONFIG).await?;
+    write_new(&dir.join("assets/icon.png"), sample_image()?).await?;
+    create_dir_all(dir.join("src/shared"))
+        .await
+        .context("Failed to create write directory")?;
+    update_gitignore(&dir.join(".gitignore")).await?;
+    write_new(&dir.join(".github/workflows/asphalt.yml"), WORKFLOW).await?;
+
+    info!(
+        "Created a new Asphalt project in {}. Set your creator ID in asphalt.toml, then run {} to upload your assets to Roblox.",
+        dir.display(),
+        style("asphalt sync").green()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+use log::info;
+use serde::{Deserialize, Serialize};
+use tokio::fs::{copy, create_dir_all, read_to_string, remove_dir_all, remove_file, write};
+use walkdir::WalkDir;
+
+use crate::{lockfile, SyncConfig};
+
+static SNAPSHOT_DIR: &str = ".asphalt/snapshots";
+static INFO_FILE_NAME: &str = "snapshot.toml";
+static GENERATED_DIR: &str = "generated";
+static GENERATED_TS_DIR: &str = "generated-ts";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotInfo {
+    /// When the snapshot was created, in seconds since the Unix epoch.
+    created_at: u64,
+    version: String,
+}
+
+/// The directories code generation writes to, with the name each is stored
+/// under in a snapshot.
+fn generated_dirs(config: &SyncConfig) -> Vec<(&'static str, PathBuf)> {
+    let mut dirs = vec![(GENERATED_DIR, PathBuf::from(&config.write_dir))];
+
+    if let Some(ts_write_dir) = &config.ts_write_dir {
+        dirs.push((GENERATED_TS_DIR, PathBuf::from(ts_write_dir)));
+    }
+
+    dirs
+}
+
+fn snapshot_path(name: &str) -> anyhow::Result<PathBuf> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        bail!("{name} is not a valid snapshot name");
+    }
+
+    Ok(Path::new(SNAPSHOT_DIR).join(name))
+}
+
+/// Copies a file, or every file in a directory, creating parent directories
+/// as needed.
+async fn copy_path(from: &Path, to: &Path) -> anyhow::Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry?;
+        if !entry.path().is_file() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(from)?;
+        let destination = if relative.as_os_str().is_empty() {
+            to.to_path_buf()
+        } else {
+            to.join(relative)
+        };
+
+        if let Some(parent) = destination.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        copy(entry.path(), &destination).await.with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                entry.path().display(),
+                destination.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+async fn create(name: &str) -> anyhow::Result<()> {
+    let config = SyncConfig::read().await.context("Failed to read config")?;
+    let snapshot_path = snapshot_path(name)?;
+
+    if snapshot_path.exists() {
+        bail!("Snapshot {name} already exists");
+    }
+
+    let lockfile_path = Path::new(lockfile::FILE_NAME);
+    if !lockfile_path.exists() {
+        bail!("There is no {} to snapshot", lockfile::FILE_NAME);
+    }
+
+    create_dir_all(&snapshot_path)
+        .await
+        .context("Failed to create snapshot directory")?;
+
+    copy_path(lockfile_path, &snapshot_path.join(lockfile::FILE_NAME)).await?;
+
+    for (snapshot_dir, dir) in generated_dirs(&config) {
+        for path in config.codegen.output_paths() {
+            let source = dir.join(&path);
+            if source.exists() {
+                copy_path(&source, &snapshot_path.join(snapshot_dir).join(&path)).await?;
+            }
+        }
+    }
+
+    let info = SnapshotInfo {
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    write(snapshot_path.join(INFO_FILE_NAME), toml::to_string(&info)?)
+        .await
+        .context("Failed to write snapshot info")?;
+
+    info!("Created snapshot {name}");
+
+    Ok(())
+}
+
+async fn restore(name: &str) -> anyhow::Result<()> {
+    let config = SyncConfig::read().await.context("Failed to read config")?;
+    let snapshot_path = snapshot_path(name)?;
+
+    let info_content = read_to_string(snapshot_path.join(INFO_FILE_NAME))
+        .await
+        .with_context(|| format!("Snapshot {name} does not exist"))?;
+    let info: SnapshotInfo =
+        toml::from_str(&info_content).context("Failed to parse snapshot info")?;
+
+    copy_path(
+        &snapshot_path.join(lockfile::FILE_NAME),
+        Path::new(lockfile::FILE_NAME),
+    )
+    .await?;
+
+    for (snapshot_dir, dir) in generated_dirs(&config) {
+        for path in config.codegen.output_paths() {
+            let source = snapshot_path.join(snapshot_dir).join(&path);
+            if !source.exists() {
+                continue;
+            }
+
+            // Remove what is there first, so split modules that were added
+            // since the snapshot don't linger.
+            let destination = dir.join(&path);
+            if destination.is_dir() {
+                remove_dir_all(&destination).await?;
+            } else if destination.is_file() {
+                remove_file(&destination).await?;
+            }
+
+            copy_path(&source, &destination).await?;
+        }
+    }
+
+    info!(
+        "Restored snapshot {name}, created by Asphalt {} at {}",
+        info.version, info.created_at
+    );
+
+    Ok(())
+}
+
+pub async fn snapshot(args: SnapshotArgs) -> anyhow::Result<()> {
+    match args.command {
+        SnapshotCommand::Create { name } => create(&name).await,
+        SnapshotCommand::Restore { name } => restore(&name).await,
+    }
+}
+
+#[derive(clap::Args)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub command: SnapshotCommand,
+}
+
+#[derive(clap::Subcommand)]
+pub enum SnapshotCommand {
+    /// Archive the lockfile and generated code.
+    Create {
+        /// The name of the snapshot, such as a release version.
+        name: String,
+    },
+
+    /// Replace the lockfile and generated code with a snapshot.
+    Restore {
+        /// The name of the snapshot to restore.
+        name: String,
+    },
+}
@@ -1,10 +1,11 @@
 use anyhow::Context;
 use log::info;
+use std::time::Instant;
 
 use crate::{
     asset::{Asset, AssetKind, ModelKind},
     commands::sync::state::SyncState,
-    upload::{upload_animation, upload_cloud_asset},
+    upload::{ensure_online, upload_animation, upload_cloud_asset},
 };
 
 use super::{SyncBackend, SyncResult};
@@ -25,11 +26,19 @@ impl SyncBackend for CloudBackend {
             }
         }
 
+        ensure_online(state.offline, "upload an asset")
+            .with_context(|| format!("Failed to upload {path}"))?;
+
+        let started = Instant::now();
         let asset_id = match asset.kind() {
             AssetKind::Decal(_) | AssetKind::Audio(_) | AssetKind::Model(ModelKind::Model) => {
-                let cloud_type = asset
-                    .cloud_type()
-                    .ok_or_else(|| anyhow::anyhow!("Invalid cloud type"))?;
+                let cloud_type = asset.cloud_type().with_context(|| {
+                    format!(
+                        "{} assets cannot be uploaded from .{} files with Open Cloud",
+                        asset.kind().asset_type(),
+                        asset.extension()
+                    )
+                })?;
 
                 upload_cloud_asset(
                     asset.data().to_owned(),
@@ -37,6 +46,7 @@ impl SyncBackend for CloudBackend {
                     cloud_type,
                     state.api_key.to_owned(),
                     state.creator.to_owned(),
+                    state.upload_prices.get(&asset.kind().asset_type()).copied(),
                 )
                 .await
             }
@@ -58,6 +68,7 @@ impl SyncBackend for CloudBackend {
             }
         }
         .with_context(|| format!("Failed to upload {path}"))?;
+        state.trace("upload", started, Some(path));
 
         info!("Uploaded {path}");
         Ok(SyncResult::Cloud(asset_id))
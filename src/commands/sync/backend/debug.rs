@@ -20,7 +20,11 @@ pub struct DebugBackend {
 
 impl DebugBackend {
     pub async fn new() -> anyhow::Result<Self> {
-        let debug_path = env::current_dir()?.join(".asphalt-debug");
+        Self::at(env::current_dir()?.join(".asphalt-debug")).await
+    }
+
+    /// Syncs assets to a folder, emptying it first.
+    pub async fn at(debug_path: PathBuf) -> anyhow::Result<Self> {
         info!("Assets will be synced to: {}", debug_path.display());
 
         if debug_path.exists() {
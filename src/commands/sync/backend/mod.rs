@@ -47,3 +47,88 @@ async fn write_to_path(dest_path: &Path, asset_path: &Path, data: &[u8]) -> anyh
         .await
         .with_context(|| format!("Failed to write asset to {}", asset_path.display()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{cloud::CloudBackend, debug::DebugBackend, studio::StudioBackend, *};
+    use crate::{
+        asset::ProcessOptions,
+        commands::sync::{SyncOptions, TargetBackend},
+        upload::is_offline_error,
+        LockFile, SyncConfig,
+    };
+    use image::{ImageFormat, RgbaImage};
+    use std::{env, io::Cursor, process};
+
+    /// An offline sync of a project in a temporary directory, with the path
+    /// and asset of a new image in it.
+    async fn offline_sync(name: &str) -> (SyncState, PathBuf, String, Asset) {
+        let dir = env::temp_dir().join(format!("asphalt-offline-{name}-{}", process::id()));
+        let mut config = SyncConfig::codegen_only(dir.join("out").to_str().unwrap().to_string());
+        config.asset_dir = dir.join("assets").to_str().unwrap().to_string();
+
+        let mut options = SyncOptions::new(config, LockFile::default());
+        options.api_key = Some(String::new());
+        options.offline = true;
+        let state = SyncState::new(options).await.unwrap();
+
+        let mut data = Vec::new();
+        RgbaImage::new(4, 4)
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        let path = format!("{}ui/gear.png", state.asset_dir.to_str().unwrap());
+        let asset = Asset::new(
+            "gear.png".to_string(),
+            data,
+            "png",
+            state.fontdb.clone(),
+            None,
+            &ProcessOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        (state, dir, path, asset)
+    }
+
+    #[tokio::test]
+    async fn only_cloud_needs_the_network() {
+        // Each target, and the folder it syncs to, if it works offline.
+        let cases = [
+            ("cloud", None),
+            ("studio", Some("content/.asphalt-test")),
+            ("debug", Some(".asphalt-debug")),
+        ];
+
+        for (target, sync_dir) in cases {
+            let (mut state, dir, path, asset) = offline_sync(target).await;
+            let backend = match target {
+                "cloud" => TargetBackend::Cloud(CloudBackend),
+                "studio" => TargetBackend::Studio(
+                    StudioBackend::at(&dir.join("content"), ".asphalt-test".to_string())
+                        .await
+                        .unwrap(),
+                ),
+                "debug" => TargetBackend::Debug(
+                    DebugBackend::at(dir.join(".asphalt-debug")).await.unwrap(),
+                ),
+                _ => unreachable!("{target} is not a sync target"),
+            };
+
+            let result = match &backend {
+                TargetBackend::Cloud(backend) => backend.sync(&mut state, &path, asset).await,
+                TargetBackend::Studio(backend) => backend.sync(&mut state, &path, asset).await,
+                TargetBackend::Debug(backend) => backend.sync(&mut state, &path, asset).await,
+            };
+            match sync_dir {
+                Some(sync_dir) => {
+                    assert!(result.is_ok(), "{target}");
+                    assert!(dir.join(sync_dir).join("ui/gear.png").exists(), "{target}");
+                }
+                None => assert!(is_offline_error(&result.err().unwrap()), "{target}"),
+            }
+
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
-use log::{debug, info, warn};
+use log::{debug, info};
 use roblox_install::RobloxStudio;
 use tokio::fs::remove_dir_all;
 
@@ -41,7 +44,13 @@ impl StudioBackend {
             .join("-");
 
         let identifier = format!(".asphalt-{}", project_name);
-        let sync_path = studio.content_path().join(&identifier);
+        Self::at(studio.content_path(), identifier).await
+    }
+
+    /// Syncs assets to a folder named `identifier` in a Studio content
+    /// folder, emptying it first.
+    pub async fn at(content_path: &Path, identifier: String) -> anyhow::Result<Self> {
+        let sync_path = content_path.join(&identifier);
         info!("Assets will be synced to: {}", sync_path.display());
 
         if sync_path.exists() {
@@ -81,7 +90,7 @@ impl SyncBackend for StudioBackend {
                 )));
             }
 
-            warn!("Animations cannot be synced as a file, please upload it first using the 'cloud' target");
+            state.warn("Animations cannot be synced as a file, please upload it first using the 'cloud' target")?;
             return Ok(SyncResult::None);
         }
 
@@ -0,0 +1,130 @@
+//! Estimates how much Robux a sync will spend on paid uploads, so that an
+//! accidental glob can be caught before anything is uploaded.
+
+use super::{fix_path, state::SyncState};
+use crate::asset::{Asset, AssetType};
+use anyhow::{bail, Context};
+use inquire::Confirm;
+use log::info;
+use std::{
+    collections::BTreeMap,
+    io::{stdin, IsTerminal},
+};
+use tokio::fs::read;
+use walkdir::DirEntry;
+
+/// Processes every asset that would be paid for, keeping them for the sync
+/// itself.
+async fn estimate_spend(
+    state: &mut SyncState,
+    entries: &[DirEntry],
+) -> anyhow::Result<BTreeMap<AssetType, (usize, u64)>> {
+    let mut spend = BTreeMap::new();
+
+    for entry in entries {
+        let path = entry.path();
+        let path_str = path.to_str().unwrap();
+
+        let Some(ext) = path.extension().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        let type_override = state.type_override(path_str)?;
+        let Some(asset_type) = type_override.or_else(|| AssetType::from_extension(ext)) else {
+            continue;
+        };
+        let Some(&price) = state.upload_prices.get(&asset_type) else {
+            continue;
+        };
+
+        let fixed_path = fix_path(path_str);
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let data = read(path)
+            .await
+            .with_context(|| format!("Failed to read {fixed_path}"))?;
+
+        let variants = state.processing.assets(path_str, &fixed_path, ext)?;
+        for (asset_path, process_options) in variants {
+            // Assets that fail to process are reported by the sync itself.
+            let Ok(mut asset) = Asset::new(
+                file_name.clone(),
+                data.clone(),
+                ext,
+                state.fontdb.clone(),
+                type_override,
+                &process_options,
+            )
+            .await
+            else {
+                continue;
+            };
+            if let Some(optimization) = state.optimize_png {
+                if asset.optimize(optimization).is_err() {
+                    continue;
+                }
+            }
+
+            let unchanged = state
+                .existing_lockfile
+                .entries
+                .get(&asset_path)
+                .is_some_and(|entry| entry.hash == asset.hash());
+            if !unchanged {
+                let (count, total) = spend.entry(asset_type).or_insert((0, 0));
+                *count += 1;
+                *total += price;
+            }
+
+            state.processed_assets.insert(asset_path, asset);
+        }
+    }
+
+    Ok(spend)
+}
+
+/// Summarizes the paid uploads a sync will make, and makes sure the user is
+/// happy to pay for them, either with a budget or an interactive prompt.
+pub async fn confirm_spend(state: &mut SyncState, entries: &[DirEntry]) -> anyhow::Result<()> {
+    if state.upload_prices.is_empty() {
+        return Ok(());
+    }
+
+    let spend = estimate_spend(state, entries).await?;
+
+    let total: u64 = spend.values().map(|(_, cost)| cost).sum();
+    if total == 0 {
+        return Ok(());
+    }
+
+    for (asset_type, (count, cost)) in &spend {
+        info!(
+            "{count} paid {asset_type} upload{} will cost {cost} Robux",
+            if *count == 1 { "" } else { "s" }
+        );
+    }
+
+    if state.dry_run {
+        return Ok(());
+    }
+
+    match state.budget {
+        Some(budget) if total > budget => {
+            bail!("This sync would spend {total} Robux, which is over the budget of {budget} Robux")
+        }
+        Some(_) => Ok(()),
+        None if stdin().is_terminal() => {
+            let confirmed = Confirm::new(&format!("Spend {total} Robux on uploads?"))
+                .with_default(false)
+                .prompt()?;
+
+            if !confirmed {
+                bail!("Paid uploads were not confirmed");
+            }
+
+            Ok(())
+        }
+        None => bail!(
+            "This sync would spend {total} Robux. Pass --budget to allow paid uploads when not running interactively"
+        ),
+    }
+}
@@ -4,7 +4,7 @@ macro_rules! proxy_display {
     ( $target: ty ) => {
         impl fmt::Display for $target {
             fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
-                let mut stream = AstStream::new(output, &self.1);
+                let mut stream = AstStream::new(output, &self.1, self.2);
                 AstFormat::fmt_ast(self, &mut stream)
             }
         }
@@ -23,23 +23,52 @@ trait AstFormat {
 #[derive(Debug)]
 pub(crate) enum AstTarget {
     Luau,
-    Typescript { output_dir: String },
+    /// Luau annotated with a type that narrows every asset to its literal value.
+    TypedLuau,
+    /// The type annotation of [`AstTarget::TypedLuau`].
+    LuauType,
+    Typescript {
+        output_dir: String,
+    },
+    /// A Typescript module whose values exist at runtime, rather than only
+    /// being declared.
+    TypescriptSource {
+        output_dir: String,
+    },
+    Json,
+}
+
+static LUAU_TYPE_TARGET: AstTarget = AstTarget::LuauType;
+
+/// Whitespace and punctuation choices that don't change what code means.
+#[derive(Debug)]
+pub(crate) struct AstFormatting {
+    /// What a single level of indentation is written as.
+    pub indent: String,
+    /// Whether the last entry of a Luau table is followed by a comma.
+    pub trailing_commas: bool,
 }
 
 pub(crate) struct AstStream<'a, 'b> {
     indents: usize,
     is_start_of_line: bool,
-    writer: &'a mut (dyn Write),
+    writer: &'a mut dyn Write,
     target: &'b AstTarget,
+    formatting: &'b AstFormatting,
 }
 
 impl<'a, 'b> AstStream<'a, 'b> {
-    pub fn new(writer: &'a mut (dyn fmt::Write + 'a), target: &'b AstTarget) -> Self {
+    pub fn new(
+        writer: &'a mut (dyn fmt::Write + 'a),
+        target: &'b AstTarget,
+        formatting: &'b AstFormatting,
+    ) -> Self {
         Self {
             indents: 0,
             is_start_of_line: true,
             writer,
             target,
+            formatting,
         }
     }
 
@@ -73,8 +102,9 @@ impl Write for AstStream<'_, '_> {
             if !line.is_empty() {
                 if self.is_start_of_line {
                     self.is_start_of_line = false;
-                    self.writer
-                        .write_str(&format!("{:\t>1$}", "", self.indents))?;
+                    for _ in 0..self.indents {
+                        self.writer.write_str(&self.formatting.indent)?;
+                    }
                 }
 
                 self.writer.write_str(line)?;
@@ -85,36 +115,69 @@ impl Write for AstStream<'_, '_> {
     }
 }
 
-proxy_display!(ReturnStatement);
+proxy_display!(ReturnStatement<'_>);
 
 #[derive(Debug)]
-pub(crate) struct ReturnStatement(pub Expression, pub AstTarget);
+pub(crate) struct ReturnStatement<'a>(pub &'a Expression, pub AstTarget, pub &'a AstFormatting);
 
-impl AstFormat for ReturnStatement {
+impl AstFormat for ReturnStatement<'_> {
     fn fmt_ast(&self, output: &mut AstStream) -> fmt::Result {
         match output.target {
-            AstTarget::Luau => {
-                write!(output, "return ")
+            AstTarget::Luau | AstTarget::LuauType => {
+                write!(output, "return ")?;
+                self.0.fmt_ast(output)?;
+                writeln!(output)
+            }
+            AstTarget::TypedLuau => {
+                write!(output, "local assets: ")?;
+
+                let target = output.target;
+                output.target = &LUAU_TYPE_TARGET;
+                self.0.fmt_ast(output)?;
+                output.target = target;
+
+                write!(output, " = ")?;
+                self.0.fmt_ast(output)?;
+                write!(
+                    output,
+                    "\n\nexport type Assets = typeof(assets)\n\nreturn assets\n"
+                )
             }
             AstTarget::Typescript { output_dir } => {
-                write!(output, "declare const {output_dir}: ")
+                write!(output, "declare const {output_dir}: ")?;
+                self.0.fmt_ast(output)?;
+                write!(output, "\nexport = {output_dir};\n")
+            }
+            AstTarget::TypescriptSource { output_dir } => {
+                write!(output, "const {output_dir} = ")?;
+                self.0.fmt_ast(output)?;
+                write!(output, " as const;\n\nexport = {output_dir};\n")
+            }
+            AstTarget::Json => {
+                self.0.fmt_ast(output)?;
+                writeln!(output)
             }
-        }?;
-
-        let result = self.0.fmt_ast(output);
-        if let AstTarget::Typescript { output_dir } = output.target {
-            write!(output, "\nexport = {output_dir};\n")?
-        } else {
-            writeln!(output)?;
         }
-        result
     }
 }
 
 #[derive(Debug)]
 pub(crate) enum Expression {
     String(String),
+    Number(u64),
     Table(Table),
+    /// The module generated for a folder, which sits next to this module.
+    Require(String),
+    /// An asset URI wrapped in a `Content` object.
+    Content(String),
+    /// A function that looks up assets by their path in a flat table. It is
+    /// left out of JSON, which has no functions.
+    Lookup(Box<Expression>),
+    /// A list of values, such as assets to preload in order.
+    Array(Vec<Expression>),
+    /// A table value followed by a comment, such as the file an asset came
+    /// from. The comment is left out of JSON and Luau types.
+    Commented(Box<Expression>, String),
 }
 
 impl Expression {
@@ -128,6 +191,47 @@ impl AstFormat for Expression {
         match self {
             Self::Table(val) => val.fmt_ast(output),
             Self::String(val) => val.fmt_ast(output),
+            // Luau has no literal types for numbers.
+            Self::Number(_) if matches!(output.target, AstTarget::LuauType) => {
+                write!(output, "number")
+            }
+            Self::Number(val) => write!(output, "{val}"),
+            Self::Require(name) => match output.target {
+                AstTarget::Luau | AstTarget::TypedLuau => write_require(output, name),
+                AstTarget::LuauType => {
+                    write!(output, "typeof(")?;
+                    write_require(output, name)?;
+                    write!(output, ")")
+                }
+                AstTarget::Typescript { .. } => {
+                    write!(output, "typeof import(")?;
+                    write_string(output, &format!("./{name}"))?;
+                    write!(output, ")")
+                }
+                AstTarget::TypescriptSource { .. } => {
+                    write!(output, "require(")?;
+                    write_string(output, &format!("./{name}"))?;
+                    write!(output, ")")
+                }
+                AstTarget::Json => write_string(output, &format!("./{name}")),
+            },
+            Self::Content(uri) => match output.target {
+                AstTarget::Luau | AstTarget::TypedLuau | AstTarget::TypescriptSource { .. } => {
+                    match uri.strip_prefix("rbxassetid://") {
+                        Some(id) => write!(output, "Content.fromAssetId({id})"),
+                        None => {
+                            write!(output, "Content.fromUri(")?;
+                            write_string(output, uri)?;
+                            write!(output, ")")
+                        }
+                    }
+                }
+                AstTarget::LuauType | AstTarget::Typescript { .. } => write!(output, "Content"),
+                AstTarget::Json => uri.fmt_ast(output),
+            },
+            Self::Lookup(paths) => write_lookup(output, paths),
+            Self::Array(values) => write_array(output, values),
+            Self::Commented(value, _) => value.fmt_ast(output),
         }
     }
 
@@ -135,8 +239,151 @@ impl AstFormat for Expression {
         match self {
             Self::Table(val) => val.fmt_key(output),
             Self::String(val) => val.fmt_key(output),
+            Self::Number(val) if matches!(output.target, AstTarget::Json) => {
+                write!(output, "\"{val}\"")
+            }
+            Self::Number(val) => write!(output, "[{val}]"),
+            Self::Require(_) | Self::Content(_) | Self::Lookup(_) | Self::Array(_) => {
+                write!(output, "[")?;
+                self.fmt_ast(output)?;
+                write!(output, "]")
+            }
+            Self::Commented(value, _) => value.fmt_key(output),
+        }
+    }
+}
+
+fn write_lookup(output: &mut AstStream, paths: &Expression) -> fmt::Result {
+    // Assets are only known to be strings when nothing else was asked for.
+    let strings = match paths {
+        Expression::Table(table) => table.expressions.iter().all(|(_, value)| match value {
+            Expression::Commented(value, _) => matches!(**value, Expression::String(_)),
+            value => matches!(value, Expression::String(_)),
+        }),
+        _ => false,
+    };
+
+    match output.target {
+        AstTarget::Luau | AstTarget::TypedLuau => {
+            writeln!(output, "(function()")?;
+            output.indent();
+            write!(output, "local paths = ")?;
+            paths.fmt_ast(output)?;
+            write!(output, "\n\nreturn function(path: string)\n")?;
+            output.indent();
+            writeln!(output, "return paths[path]")?;
+            output.unindent();
+            writeln!(output, "end")?;
+            output.unindent();
+            write!(output, "end)()")
+        }
+        AstTarget::LuauType => {
+            let value_type = if strings { "string" } else { "any" };
+            write!(output, "(path: string) -> {value_type}?")
+        }
+        AstTarget::Typescript { .. } => {
+            let value_type = if strings { "string" } else { "unknown" };
+            write!(output, "(path: string) => {value_type} | undefined")
+        }
+        AstTarget::TypescriptSource { .. } => {
+            let value_type = if strings { "string" } else { "unknown" };
+            write!(
+                output,
+                "((paths: Record<string, {value_type}>) => (path: string): {value_type} | undefined => paths[path])("
+            )?;
+            paths.fmt_ast(output)?;
+            write!(output, ")")
+        }
+        // Left out by the table that contains it.
+        AstTarget::Json => Ok(()),
+    }
+}
+
+fn write_array(output: &mut AstStream, values: &[Expression]) -> fmt::Result {
+    let strings = values
+        .iter()
+        .all(|value| matches!(value, Expression::String(_)));
+    let numbers = !values.is_empty()
+        && values
+            .iter()
+            .all(|value| matches!(value, Expression::Number(_)));
+
+    let (open, close) = match output.target {
+        AstTarget::LuauType => {
+            let value_type = match (strings, numbers) {
+                (true, _) => "string",
+                (_, true) => "number",
+                _ => "any",
+            };
+            return write!(output, "{{{value_type}}}");
+        }
+        AstTarget::Typescript { .. } => {
+            let value_type = match (strings, numbers) {
+                (true, _) => "string",
+                (_, true) => "number",
+                _ => "unknown",
+            };
+            return write!(output, "{value_type}[]");
+        }
+        AstTarget::Luau | AstTarget::TypedLuau => ("{", "}"),
+        AstTarget::TypescriptSource { .. } | AstTarget::Json => ("[", "]"),
+    };
+
+    if values.is_empty() {
+        return write!(output, "{open}{close}");
+    }
+
+    writeln!(output, "{open}")?;
+    output.indent();
+
+    let luau = matches!(output.target, AstTarget::Luau | AstTarget::TypedLuau);
+    for (index, value) in values.iter().enumerate() {
+        value.fmt_ast(output)?;
+
+        let is_last = index == values.len() - 1;
+        if is_last && !(luau && output.formatting.trailing_commas) {
+            writeln!(output)?;
+        } else {
+            writeln!(output, ",")?;
+        }
+    }
+
+    output.unindent();
+    write!(output, "{close}")
+}
+
+fn write_require(output: &mut AstStream, name: &str) -> fmt::Result {
+    if is_valid_luau_identifier(name) {
+        write!(output, "require(script.{name})")
+    } else {
+        write!(output, "require(script[")?;
+        write_string(output, name)?;
+        write!(output, "])")
+    }
+}
+
+/// Writes a quoted string literal, escaping anything that would end it early
+/// or can't appear in it literally, like quotes and newlines.
+fn write_string(output: &mut AstStream, value: &str) -> fmt::Result {
+    let luau = matches!(
+        output.target,
+        AstTarget::Luau | AstTarget::TypedLuau | AstTarget::LuauType
+    );
+
+    write!(output, "\"")?;
+    for char in value.chars() {
+        match char {
+            '"' => write!(output, "\\\"")?,
+            '\\' => write!(output, "\\\\")?,
+            '\n' => write!(output, "\\n")?,
+            '\r' => write!(output, "\\r")?,
+            '\t' => write!(output, "\\t")?,
+            char if char.is_control() && luau => write!(output, "\\u{{{:x}}}", char as u32)?,
+            char if char.is_control() => write!(output, "\\u{:04x}", char as u32)?,
+            char => write!(output, "{char}")?,
         }
     }
+    write!(output, "\"")
 }
 
 #[derive(Debug)]
@@ -147,16 +394,25 @@ pub(crate) struct Table {
 impl AstFormat for Table {
     fn fmt_ast(&self, output: &mut AstStream<'_, '_>) -> fmt::Result {
         let typescript = matches!(output.target, AstTarget::Typescript { .. });
-        let (assignment, ending) = if typescript {
-            (": ", ";")
-        } else {
-            (" = ", ",")
+        let json = matches!(output.target, AstTarget::Json);
+        let (assignment, ending) = match output.target {
+            AstTarget::Typescript { .. } => (": ", ";"),
+            AstTarget::LuauType | AstTarget::TypescriptSource { .. } | AstTarget::Json => {
+                (": ", ",")
+            }
+            AstTarget::Luau | AstTarget::TypedLuau => (" = ", ","),
         };
 
+        let expressions: Vec<_> = self
+            .expressions
+            .iter()
+            .filter(|(_, value)| !(json && matches!(value, Expression::Lookup(_))))
+            .collect();
+
         writeln!(output, "{{")?;
         output.indent();
 
-        for (key, value) in &self.expressions {
+        for (index, (key, value)) in expressions.iter().enumerate() {
             key.fmt_key(output)?;
             write!(output, "{assignment}")?;
             value.fmt_ast(output)?;
@@ -170,7 +426,22 @@ impl AstFormat for Table {
                 }
             }
 
-            writeln!(output, "{ending}")?;
+            // JSON does not allow a trailing separator after the last entry.
+            let is_last = index == expressions.len() - 1;
+            if !(is_last && (json || !(typescript || output.formatting.trailing_commas))) {
+                write!(output, "{ending}")?;
+            }
+
+            if let Expression::Commented(_, comment) = value {
+                match output.target {
+                    AstTarget::Luau | AstTarget::TypedLuau => write!(output, " -- {comment}")?,
+                    AstTarget::Typescript { .. } | AstTarget::TypescriptSource { .. } => {
+                        write!(output, " // {comment}")?
+                    }
+                    AstTarget::LuauType | AstTarget::Json => {}
+                }
+            }
+            writeln!(output)?;
         }
 
         output.unindent();
@@ -184,16 +455,28 @@ impl AstFormat for Table {
 
 impl AstFormat for String {
     fn fmt_ast(&self, output: &mut AstStream) -> fmt::Result {
-        write!(output, "\"{}\"", self)
+        write_string(output, self)
     }
 
     fn fmt_key(&self, output: &mut AstStream<'_, '_>) -> fmt::Result {
-        if is_valid_identifier(self) {
-            write!(output, "{}", self)
-        } else {
-            match output.target {
-                AstTarget::Luau => write!(output, "[\"{}\"]", self),
-                AstTarget::Typescript { .. } => write!(output, "\"{}\"", self),
+        match output.target {
+            AstTarget::Json => write_string(output, self),
+            AstTarget::Luau | AstTarget::TypedLuau | AstTarget::LuauType => {
+                if is_valid_luau_identifier(self) {
+                    write!(output, "{}", self)
+                } else {
+                    write!(output, "[")?;
+                    write_string(output, self)?;
+                    write!(output, "]")
+                }
+            }
+            // Reserved words can be property names in Typescript.
+            AstTarget::Typescript { .. } | AstTarget::TypescriptSource { .. } => {
+                if is_valid_identifier(self) {
+                    write!(output, "{}", self)
+                } else {
+                    write_string(output, self)
+                }
             }
         }
     }
@@ -245,3 +528,14 @@ fn is_valid_identifier(value: &str) -> bool {
 
     chars.all(is_valid_ident_char)
 }
+
+/// Words Luau reserves, which can't be used as names even though they look
+/// like them.
+const LUAU_KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local",
+    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+fn is_valid_luau_identifier(value: &str) -> bool {
+    is_valid_identifier(value) && !LUAU_KEYWORDS.contains(&value)
+}
@@ -0,0 +1,48 @@
+use super::{ast::Expression, CodegenOptions};
+use anyhow::Context;
+use globset::{Glob, GlobSetBuilder};
+use std::collections::BTreeMap;
+
+/// Builds a table of bundles, each with the assets matching its globs keyed
+/// by their path relative to the asset directory, and the total size of
+/// those assets when it is known.
+pub fn generate_table(
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<Expression> {
+    let mut bundles = Vec::new();
+
+    for (name, globs) in &options.bundles {
+        let mut builder = GlobSetBuilder::new();
+        for glob in globs {
+            builder.add(Glob::new(glob).with_context(|| format!("Invalid glob in bundle {name}"))?);
+        }
+        let matcher = builder.build()?;
+
+        let mut entries = Vec::new();
+        let mut bytes = 0;
+        for (path, uri) in assets {
+            if !matcher.is_match(path) {
+                continue;
+            }
+
+            let key = path
+                .strip_prefix(strip_dir)
+                .unwrap_or(path)
+                .trim_start_matches('/');
+            entries.push((key.into(), Expression::String(uri.clone())));
+            bytes += options.sizes.get(path).copied().unwrap_or_default();
+        }
+
+        let mut fields = vec![("assets".into(), Expression::table(entries))];
+        // Sizes are only known when assets have been processed.
+        if !options.sizes.is_empty() {
+            fields.push(("bytes".into(), Expression::Number(bytes as u64)));
+        }
+
+        bundles.push((name.into(), Expression::table(fields)));
+    }
+
+    Ok(Expression::table(bundles))
+}
@@ -0,0 +1,56 @@
+use super::{ast::Expression, flat};
+use crate::asset::AssetType;
+use std::{collections::BTreeMap, path::Path};
+
+/// The key assets of a type are grouped under.
+fn group_name(asset_type: Option<AssetType>) -> &'static str {
+    match asset_type {
+        Some(AssetType::Decal) => "images",
+        Some(AssetType::Audio) => "audio",
+        Some(AssetType::Model) => "models",
+        Some(AssetType::Animation) => "animations",
+        None => "other",
+    }
+}
+
+/// The type of an asset. Types not known from syncing are guessed from the
+/// file extension.
+pub(super) fn asset_type(
+    path: &str,
+    asset_types: &BTreeMap<String, AssetType>,
+) -> Option<AssetType> {
+    asset_types.get(path).copied().or_else(|| {
+        Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(AssetType::from_extension)
+    })
+}
+
+/// Builds a table of flat tables, one for each type of asset. Types not known
+/// from syncing are guessed from the file extension.
+pub fn generate_table(
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    strip_extension: bool,
+    asset_types: &BTreeMap<String, AssetType>,
+) -> anyhow::Result<Expression> {
+    let mut groups: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+
+    for (path, asset_id) in assets {
+        groups
+            .entry(group_name(asset_type(path, asset_types)))
+            .or_default()
+            .insert(path.clone(), asset_id.clone());
+    }
+
+    let mut expressions = Vec::new();
+    for (name, entries) in groups {
+        expressions.push((
+            name.into(),
+            flat::generate_table(&entries, strip_dir, strip_extension)?,
+        ));
+    }
+
+    Ok(Expression::table(expressions))
+}
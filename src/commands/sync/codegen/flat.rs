@@ -1,13 +1,10 @@
-use anyhow::Context;
+use anyhow::{bail, Context};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
 };
 
-use super::{
-    ast::{AstTarget, Expression},
-    generate_code,
-};
+use super::ast::Expression;
 
 fn asset_path(file_path: &str, strip_dir: &str, strip_extension: bool) -> anyhow::Result<String> {
     if strip_extension {
@@ -22,44 +19,22 @@ fn asset_path(file_path: &str, strip_dir: &str, strip_extension: bool) -> anyhow
     .map(|s| s.to_string())
 }
 
-fn generate_table(
+pub fn generate_table(
     assets: &BTreeMap<String, String>,
     strip_dir: &str,
     strip_extension: bool,
 ) -> anyhow::Result<Expression> {
     let mut expressions: Vec<(Expression, Expression)> = Vec::new();
-    for (file_path, asset_id) in assets.iter() {
+    let mut seen: HashMap<String, &String> = HashMap::new();
+    for file_path in assets.keys() {
         let file_stem = asset_path(file_path, strip_dir, strip_extension)?;
+        if let Some(other) = seen.insert(file_stem.clone(), file_path) {
+            bail!("{other} and {file_path} would both generate the key {file_stem}");
+        }
         expressions.push((
             Expression::String(file_stem),
-            Expression::String(asset_id.clone()),
+            Expression::String(file_path.clone()),
         ));
     }
     Ok(Expression::table(expressions))
 }
-
-pub fn generate_luau(
-    assets: &BTreeMap<String, String>,
-    strip_dir: &str,
-    strip_extension: bool,
-) -> anyhow::Result<String> {
-    let table =
-        generate_table(assets, strip_dir, strip_extension).context("Failed to generate table")?;
-    generate_code(table, AstTarget::Luau)
-}
-
-pub fn generate_ts(
-    assets: &BTreeMap<String, String>,
-    strip_dir: &str,
-    output_dir: &str,
-    strip_extension: bool,
-) -> anyhow::Result<String> {
-    let table =
-        generate_table(assets, strip_dir, strip_extension).context("Failed to generate table")?;
-    generate_code(
-        table,
-        AstTarget::Typescript {
-            output_dir: output_dir.to_owned(),
-        },
-    )
-}
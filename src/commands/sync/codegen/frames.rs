@@ -0,0 +1,95 @@
+use super::ast::Expression;
+use anyhow::bail;
+use std::collections::{BTreeMap, HashMap};
+
+/// Splits a key like `fx/run_012.png` into the key its sequence is grouped
+/// under, `fx/run.png`, and its frame number, 12.
+fn split_frame(key: &str) -> Option<(String, u64)> {
+    let (dir, name) = match key.rsplit_once('/') {
+        Some((dir, name)) => (Some(dir), name),
+        None => (None, key),
+    };
+
+    let (stem, extension) = match name.split_once('.') {
+        Some((stem, extension)) => (stem, Some(extension)),
+        None => (name, None),
+    };
+    let (base, number) = stem.rsplit_once('_')?;
+    if base.is_empty() || number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut grouped = String::new();
+    if let Some(dir) = dir {
+        grouped.push_str(dir);
+        grouped.push('/');
+    }
+    grouped.push_str(base);
+    if let Some(extension) = extension {
+        grouped.push('.');
+        grouped.push_str(extension);
+    }
+
+    Some((grouped, number.parse().ok()?))
+}
+
+/// The sequence and frame number of an asset, if it is a numbered frame.
+fn frame_of(key: &Expression, value: &Expression) -> Option<(String, u64)> {
+    match (key, value) {
+        (Expression::String(key), Expression::String(_)) => split_frame(key),
+        _ => None,
+    }
+}
+
+/// Groups numbered frames, like `run_001.png` to `run_024.png`, into a table
+/// with a `frames` array in order and the frame rate they play at. Numbered
+/// assets without another frame are left alone.
+pub fn group_frames(expression: &mut Expression, fps: Option<u32>) -> anyhow::Result<()> {
+    let Expression::Table(table) = expression else {
+        return Ok(());
+    };
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (key, value) in &mut table.expressions {
+        group_frames(value, fps)?;
+
+        if let Some((grouped, _)) = frame_of(key, value) {
+            *counts.entry(grouped).or_default() += 1;
+        }
+    }
+
+    let mut expressions = Vec::new();
+    let mut sequences: BTreeMap<String, Vec<(u64, Expression)>> = BTreeMap::new();
+    for (key, value) in std::mem::take(&mut table.expressions) {
+        match frame_of(&key, &value) {
+            Some((grouped, number)) if counts[&grouped] > 1 => {
+                sequences.entry(grouped).or_default().push((number, value));
+            }
+            _ => expressions.push((key, value)),
+        }
+    }
+
+    for (grouped, mut frames) in sequences {
+        let taken = expressions
+            .iter()
+            .any(|(key, _)| matches!(key, Expression::String(key) if *key == grouped));
+        if taken {
+            bail!("The frames of {grouped} would generate a key which is already used");
+        }
+
+        frames.sort_by_key(|(number, _)| *number);
+
+        let mut fields = vec![(
+            "frames".into(),
+            Expression::Array(frames.into_iter().map(|(_, frame)| frame).collect()),
+        )];
+        if let Some(fps) = fps {
+            fields.push(("fps".into(), Expression::Number(fps as u64)));
+        }
+        expressions.push((grouped.into(), Expression::table(fields)));
+    }
+
+    table.expressions = expressions;
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use super::ast::Expression;
+use crate::commands::sync::config::CodegenKeyStyle;
+
+/// Splits a name into words at separators and lowercase to uppercase
+/// boundaries, so `my-file`, `my_file` and `myFile` all become `my` and `file`.
+fn words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+
+    for char in value.chars() {
+        if !char.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous = None;
+            continue;
+        }
+
+        let boundary = char.is_uppercase()
+            && previous.is_some_and(|previous| previous.is_lowercase() || previous.is_numeric());
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+
+        current.push(char);
+        previous = Some(char);
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn convert_name(name: &str, style: CodegenKeyStyle) -> String {
+    let words = words(name);
+    if words.is_empty() {
+        return name.to_string();
+    }
+
+    match style {
+        CodegenKeyStyle::Preserve => name.to_string(),
+        CodegenKeyStyle::CamelCase => words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                if index == 0 {
+                    word.to_lowercase()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect(),
+        CodegenKeyStyle::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+        CodegenKeyStyle::SnakeCase => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+    }
+}
+
+/// Converts every segment of a key to the given style, leaving path
+/// separators and file extensions alone.
+pub fn convert_key(key: &str, style: CodegenKeyStyle) -> String {
+    if style == CodegenKeyStyle::Preserve {
+        return key.to_string();
+    }
+
+    key.split('/')
+        .map(|segment| match segment.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => {
+                format!("{}.{extension}", convert_name(stem, style))
+            }
+            _ => convert_name(segment, style),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Recursively converts the keys of every table in an expression, failing if
+/// two keys in the same table end up the same.
+pub fn apply_key_style(expression: &mut Expression, style: CodegenKeyStyle) -> anyhow::Result<()> {
+    if style == CodegenKeyStyle::Preserve {
+        return Ok(());
+    }
+
+    let Expression::Table(table) = expression else {
+        return Ok(());
+    };
+
+    let mut seen: HashMap<String, String> = HashMap::new();
+    for (key, value) in &mut table.expressions {
+        if let Expression::String(original) = key {
+            let converted = convert_key(original, style);
+            // Assets are still their file paths, which are more useful to
+            // point at than keys.
+            let source = match value {
+                Expression::String(path) => path.clone(),
+                _ => original.clone(),
+            };
+            if let Some(other) = seen.insert(converted.clone(), source.clone()) {
+                bail!("{other} and {source} would both generate the key {converted}");
+            }
+            *original = converted;
+        }
+
+        apply_key_style(value, style)?;
+    }
+
+    Ok(())
+}
@@ -1,48 +1,893 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use ast::{AstTarget, Expression, ReturnStatement};
+use anyhow::{bail, Context};
+use ast::{AstFormatting, AstTarget, Expression, ReturnStatement};
+use globset::{Glob, GlobSetBuilder};
+pub use node_tree::CodegenNode;
 
-use crate::commands::sync::config::CodegenStyle;
+use crate::asset::AssetType;
+use crate::commands::sync::config::{
+    CodegenConfig, CodegenIndentType, CodegenKeyStyle, CodegenSort, CodegenStyle, SpriteRect,
+    TypescriptMode,
+};
+use crate::util::flipbook::Flipbook;
 
 mod ast;
+mod bundles;
+mod by_type;
 mod flat;
+mod frames;
+mod keys;
 mod nested;
+mod node_tree;
+mod preload;
+mod sort;
+mod variants;
 
-pub fn generate_luau(
+/// Settings that control which files are generated, and their shape.
+pub struct CodegenOptions {
+    pub output_name: String,
+    pub luau: bool,
+    pub typescript: bool,
+    pub typescript_mode: TypescriptMode,
+    pub json: bool,
+    pub split_by_folder: bool,
+    pub style: CodegenStyle,
+    pub sort: CodegenSort,
+    pub key_style: CodegenKeyStyle,
+    pub strip_extension: bool,
+    pub luau_types: bool,
+    pub numeric_ids: bool,
+    pub include_dimensions: bool,
+    pub content: bool,
+    pub lookup: bool,
+    /// Written as a comment at the top of every generated file that allows
+    /// comments.
+    pub header: Option<String>,
+    /// Write an `init.luau` to the write directory that re-exports the
+    /// generated module.
+    pub init_shim: bool,
+    /// The DataModel path of the generated module, noted in the shim.
+    pub rojo_path: Option<String>,
+    /// What a single level of indentation is written as.
+    pub indent: String,
+    pub trailing_commas: bool,
+    /// Follow each asset with a comment naming the file it came from.
+    pub source_comments: bool,
+    /// Separates the name of an asset from the name of its variant, like
+    /// `@` in `icon@dark.png`.
+    pub variant_separator: Option<String>,
+    /// Group numbered frames, like `run_001.png`, into ordered arrays.
+    pub frame_sequences: bool,
+    /// The frame rate noted with each frame sequence.
+    pub frame_rate: Option<u32>,
+    /// The width and height of image assets, keyed by their paths.
+    pub dimensions: BTreeMap<String, (u32, u32)>,
+    /// The hashes of assets, keyed by their paths, for source comments.
+    pub hashes: BTreeMap<String, String>,
+    /// The types of assets, keyed by their paths, for grouping by type.
+    pub asset_types: BTreeMap<String, AssetType>,
+    /// Keys to use instead of ones derived from paths, keyed by path. Assets
+    /// without a key are left out.
+    pub keys: Option<BTreeMap<String, String>>,
+    /// Globs of the assets in each bundle, keyed by the bundle's name.
+    pub bundles: BTreeMap<String, Vec<String>>,
+    /// Globs of assets to preload, in the order they should be preloaded.
+    pub preload_order: Vec<String>,
+    /// Write a module with an array of every asset's URI, for preloading.
+    pub preload_list: bool,
+    /// The types of assets in the preload list, or every type if empty.
+    pub preload_types: Vec<AssetType>,
+    /// The parts of sprite sheets to use, keyed by the paths of assets.
+    pub rects: BTreeMap<String, SpriteRect>,
+    /// Globs of nine-slice images, mapped to the centers they are sliced
+    /// around.
+    pub slice_center_globs: BTreeMap<String, SpriteRect>,
+    /// The nine-slice centers of images, keyed by their paths.
+    pub slice_centers: BTreeMap<String, SpriteRect>,
+    /// Where the frames of animated images are, keyed by their paths.
+    pub flipbooks: BTreeMap<String, Flipbook>,
+    /// Globs of images uploaded with premultiplied alpha.
+    pub premultiplied_globs: Vec<String>,
+    /// The paths of images uploaded with premultiplied alpha.
+    pub premultiplied: BTreeSet<String>,
+    /// The processed sizes of assets in bytes, keyed by their paths.
+    pub sizes: BTreeMap<String, usize>,
+    pub meta: Option<CodegenMeta>,
+}
+
+impl CodegenOptions {
+    /// Reads options from the config, filling in defaults. Information that
+    /// is only known after syncing, like dimensions, starts out empty.
+    pub fn new(config: &CodegenConfig) -> Self {
+        Self {
+            output_name: config
+                .output_name
+                .clone()
+                .unwrap_or_else(|| "assets".to_string()),
+            luau: config.luau.unwrap_or(true),
+            typescript: config.typescript.unwrap_or(false),
+            typescript_mode: config
+                .typescript_mode
+                .unwrap_or(TypescriptMode::Declaration),
+            json: config.json.unwrap_or(false),
+            split_by_folder: config.split_by_folder.unwrap_or(false),
+            style: config.style.unwrap_or(CodegenStyle::Flat),
+            sort: config.sort.unwrap_or(CodegenSort::Bytewise),
+            key_style: config.key_style.unwrap_or(CodegenKeyStyle::Preserve),
+            strip_extension: config.strip_extension.unwrap_or(false),
+            luau_types: config.luau_types.unwrap_or(false),
+            numeric_ids: config.numeric_ids.unwrap_or(false),
+            include_dimensions: config.include_dimensions.unwrap_or(false),
+            content: config.content.unwrap_or(false),
+            lookup: config.lookup.unwrap_or(false),
+            header: None,
+            init_shim: config.init_shim.unwrap_or(false),
+            rojo_path: config.rojo_path.clone(),
+            indent: match config.indent_type.unwrap_or(CodegenIndentType::Tabs) {
+                CodegenIndentType::Tabs => "\t".to_string(),
+                CodegenIndentType::Spaces => " ".repeat(config.indent_width.unwrap_or(4)),
+            },
+            trailing_commas: config.trailing_commas.unwrap_or(true),
+            source_comments: config.source_comments.unwrap_or(false),
+            variant_separator: config.variant_separator.clone(),
+            frame_sequences: config.frame_sequences.unwrap_or(false),
+            frame_rate: config.frame_rate,
+            dimensions: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+            asset_types: BTreeMap::new(),
+            keys: None,
+            bundles: config.bundles.clone(),
+            preload_order: config.preload_order.clone(),
+            preload_list: config.preload_list.unwrap_or(false),
+            preload_types: config.preload_types.clone(),
+            rects: BTreeMap::new(),
+            slice_center_globs: config.slice_centers.clone(),
+            slice_centers: BTreeMap::new(),
+            flipbooks: BTreeMap::new(),
+            premultiplied_globs: Vec::new(),
+            premultiplied: BTreeSet::new(),
+            sizes: BTreeMap::new(),
+            meta: None,
+        }
+    }
+
+    /// Finds the nine-slice center of every asset matching a slice center
+    /// glob.
+    pub fn match_slice_centers(&mut self, assets: &BTreeMap<String, String>) -> anyhow::Result<()> {
+        let mut matchers = Vec::new();
+        for (glob, center) in &self.slice_center_globs {
+            let matcher = Glob::new(glob)
+                .with_context(|| format!("Invalid slice center glob {glob}"))?
+                .compile_matcher();
+            matchers.push((glob, matcher, *center));
+        }
+
+        for path in assets.keys() {
+            let mut found: Option<(&String, SpriteRect)> = None;
+            for (glob, matcher, center) in &matchers {
+                if !matcher.is_match(path) {
+                    continue;
+                }
+                match found {
+                    Some((other, other_center)) if other_center != *center => {
+                        bail!("Conflicting slice centers ({other} and {glob}) match {path}")
+                    }
+                    _ => found = Some((glob, *center)),
+                }
+            }
+
+            if let Some((_, center)) = found {
+                self.slice_centers.insert(path.clone(), center);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds every asset matching a premultiplied alpha glob.
+    pub fn match_premultiplied(&mut self, assets: &BTreeMap<String, String>) -> anyhow::Result<()> {
+        let mut builder = GlobSetBuilder::new();
+        for glob in &self.premultiplied_globs {
+            builder.add(
+                Glob::new(glob)
+                    .with_context(|| format!("Invalid premultiply_alpha glob {glob}"))?,
+            );
+        }
+        let matcher = builder.build()?;
+
+        self.premultiplied = assets
+            .keys()
+            .filter(|path| matcher.is_match(path))
+            .cloned()
+            .collect();
+
+        Ok(())
+    }
+
+    fn formatting(&self) -> AstFormatting {
+        AstFormatting {
+            indent: self.indent.clone(),
+            trailing_commas: self.trailing_commas,
+        }
+    }
+}
+
+/// Provenance information emitted as a `__meta` table in the generated code.
+pub struct CodegenMeta {
+    /// When the sync happened, in seconds since the Unix epoch.
+    pub synced_at: u64,
+    pub version: String,
+    pub asset_count: usize,
+}
+
+impl CodegenMeta {
+    pub fn now(asset_count: usize) -> Self {
+        Self {
+            synced_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            asset_count,
+        }
+    }
+
+    fn to_expression(&self) -> Expression {
+        Expression::table(vec![
+            (
+                "asset_count".into(),
+                Expression::Number(self.asset_count as u64),
+            ),
+            ("synced_at".into(), Expression::Number(self.synced_at)),
+            ("version".into(), self.version.as_str().into()),
+        ])
+    }
+}
+
+/// Builds the value of a single asset, which is a bare URI unless more
+/// information about it was asked for.
+fn asset_expression(path: &str, uri: &str, options: &CodegenOptions) -> Expression {
+    let url = if options.content {
+        Expression::Content(uri.to_string())
+    } else {
+        Expression::String(uri.to_string())
+    };
+
+    let id = options
+        .numeric_ids
+        .then(|| uri.strip_prefix("rbxassetid://")?.parse().ok())
+        .flatten();
+    let dimensions = options
+        .include_dimensions
+        .then(|| options.dimensions.get(path))
+        .flatten();
+
+    let rect = options.rects.get(path);
+    let slice_center = options.slice_centers.get(path);
+    let flipbook = options.flipbooks.get(path);
+    let premultiplied = options.premultiplied.contains(path);
+
+    if !options.numeric_ids
+        && dimensions.is_none()
+        && rect.is_none()
+        && slice_center.is_none()
+        && flipbook.is_none()
+        && !premultiplied
+    {
+        return url;
+    }
+
+    let mut fields = Vec::new();
+    if let Some(id) = id {
+        fields.push(("id".into(), Expression::Number(id)));
+    }
+    fields.push(("url".into(), url));
+    if let Some((width, height)) = dimensions {
+        fields.push(("width".into(), Expression::Number(*width as u64)));
+        fields.push(("height".into(), Expression::Number(*height as u64)));
+    }
+    if let Some(rect) = rect {
+        fields.push(("rect".into(), rect_expression(rect)));
+    }
+    if let Some(slice_center) = slice_center {
+        fields.push(("slice_center".into(), rect_expression(slice_center)));
+    }
+    if let Some(flipbook) = flipbook {
+        fields.push(("flipbook".into(), flipbook_expression(flipbook)));
+    }
+    if premultiplied {
+        fields.push(("alpha".into(), Expression::String("premultiplied".into())));
+    }
+
+    Expression::table(fields)
+}
+
+fn rect_expression(rect: &SpriteRect) -> Expression {
+    Expression::table(vec![
+        ("x".into(), Expression::Number(rect.x as u64)),
+        ("y".into(), Expression::Number(rect.y as u64)),
+        ("width".into(), Expression::Number(rect.width as u64)),
+        ("height".into(), Expression::Number(rect.height as u64)),
+    ])
+}
+
+fn flipbook_expression(flipbook: &Flipbook) -> Expression {
+    let durations = flipbook
+        .durations
+        .iter()
+        .map(|duration| Expression::Number(*duration as u64))
+        .collect();
+
+    Expression::table(vec![
+        (
+            "frame_width".into(),
+            Expression::Number(flipbook.frame_width as u64),
+        ),
+        (
+            "frame_height".into(),
+            Expression::Number(flipbook.frame_height as u64),
+        ),
+        (
+            "columns".into(),
+            Expression::Number(flipbook.columns as u64),
+        ),
+        ("durations".into(), Expression::Array(durations)),
+    ])
+}
+
+fn source_comment(path: &str, options: &CodegenOptions) -> String {
+    // A comment can't span lines, so anything that would end it is dropped.
+    let comment: String = path.chars().filter(|char| !char.is_control()).collect();
+
+    match options.hashes.get(path) {
+        Some(hash) => format!("{comment} (hash {})", &hash[..hash.len().min(8)]),
+        None => comment,
+    }
+}
+
+/// Replaces the file paths left in a table by the flat and nested generators
+/// with the values of their assets, commented with their paths if
+/// `comments` is set.
+fn resolve_assets(
+    expression: &mut Expression,
+    assets: &BTreeMap<String, String>,
+    options: &CodegenOptions,
+    comments: bool,
+) {
+    match expression {
+        Expression::String(path) => {
+            if let Some(uri) = assets.get(path.as_str()) {
+                let value = asset_expression(path, uri, options);
+                *expression = if comments {
+                    Expression::Commented(Box::new(value), source_comment(path, options))
+                } else {
+                    value
+                };
+            }
+        }
+        Expression::Table(table) => {
+            for (_, value) in &mut table.expressions {
+                resolve_assets(value, assets, options, comments);
+            }
+        }
+        Expression::Array(values) => {
+            for value in values {
+                resolve_assets(value, assets, options, comments);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Builds a table of the keys of assets, with the paths of the assets they
+/// are for as values.
+fn generate_keyed_table(
     assets: &BTreeMap<String, String>,
     strip_dir: &str,
-    style: &CodegenStyle,
-    strip_extension: bool,
-) -> anyhow::Result<String> {
-    match style {
-        CodegenStyle::Flat => flat::generate_luau(assets, strip_dir, strip_extension),
-        CodegenStyle::Nested => nested::generate_luau(assets, strip_dir, strip_extension),
+    options: &CodegenOptions,
+) -> anyhow::Result<Expression> {
+    let mut expression = match (&options.keys, options.style) {
+        (Some(keys), _) => {
+            let mut seen: BTreeMap<&String, &String> = BTreeMap::new();
+            let mut expressions = Vec::new();
+            for path in assets.keys() {
+                let Some(key) = keys.get(path) else {
+                    continue;
+                };
+                if let Some(other) = seen.insert(key, path) {
+                    bail!("{other} and {path} would both generate the key {key}");
+                }
+                expressions.push((key.into(), Expression::String(path.clone())));
+            }
+            Expression::table(expressions)
+        }
+        (None, CodegenStyle::Flat) => {
+            flat::generate_table(assets, strip_dir, options.strip_extension)
+                .context("Failed to generate table")?
+        }
+        (None, CodegenStyle::Nested) => {
+            nested::generate_table(assets, strip_dir, options.strip_extension)
+                .context("Failed to generate nested table")?
+        }
+        (None, CodegenStyle::ByType) => by_type::generate_table(
+            assets,
+            strip_dir,
+            options.strip_extension,
+            &options.asset_types,
+        )
+        .context("Failed to generate table by type")?,
+    };
+
+    if options.keys.is_none() && options.frame_sequences {
+        frames::group_frames(&mut expression, options.frame_rate)?;
+    }
+    if let (None, Some(separator)) = (&options.keys, &options.variant_separator) {
+        variants::group_variants(&mut expression, separator)?;
+    }
+    keys::apply_key_style(&mut expression, options.key_style)?;
+
+    Ok(expression)
+}
+
+fn generate_table(
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<Expression> {
+    let mut expression = generate_keyed_table(assets, strip_dir, options)?;
+    resolve_assets(&mut expression, assets, options, options.source_comments);
+
+    Ok(expression)
+}
+
+/// Builds the tree of keys generated code would have, without splitting it
+/// by folder, with what each asset resolved to.
+pub fn generate_node_tree(
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<CodegenNode> {
+    let expression = generate_keyed_table(assets, strip_dir, options)?;
+
+    CodegenNode::from_expression(&expression, assets, options).context("Failed to build node tree")
+}
+
+fn add_meta(expression: &mut Expression, options: &CodegenOptions) {
+    if let (Some(meta), Expression::Table(table)) = (&options.meta, expression) {
+        table
+            .expressions
+            .push(("__meta".into(), meta.to_expression()));
+    }
+}
+
+/// Adds the header as the first key of a JSON table, as JSON has no comments.
+fn add_json_header(expression: &mut Expression, options: &CodegenOptions) {
+    if let (Some(header), Expression::Table(table)) = (&options.header, expression) {
+        table
+            .expressions
+            .insert(0, ("$header".into(), Expression::String(header.clone())));
+    }
+}
+
+/// Adds a `get` function that looks up assets by their path relative to the
+/// asset directory, for paths that are only known at runtime.
+fn add_lookup(
+    expression: &mut Expression,
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<()> {
+    let Expression::Table(table) = expression else {
+        return Ok(());
+    };
+
+    let taken = table
+        .expressions
+        .iter()
+        .any(|(key, _)| matches!(key, Expression::String(key) if key == "get"));
+    if taken {
+        bail!("An asset would generate the key get, which is used by the lookup function");
+    }
+
+    let mut paths = flat::generate_table(assets, strip_dir, options.strip_extension)?;
+    if let Expression::Table(paths) = &mut paths {
+        for (key, _) in &mut paths.expressions {
+            if let Expression::String(key) = key {
+                *key = key.trim_start_matches('/').to_string();
+            }
+        }
+    }
+    resolve_assets(&mut paths, assets, options, false);
+    sort::sort_expression(&mut paths, options.sort);
+
+    table
+        .expressions
+        .push(("get".into(), Expression::Lookup(Box::new(paths))));
+
+    Ok(())
+}
+
+fn generate_expression(
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<Expression> {
+    let mut expression = generate_table(assets, strip_dir, options)?;
+    if options.lookup {
+        add_lookup(&mut expression, assets, strip_dir, options)?;
+    }
+    add_meta(&mut expression, options);
+    sort::sort_expression(&mut expression, options.sort);
+    preload::add_preload(&mut expression, assets, strip_dir, options)?;
+
+    Ok(expression)
+}
+
+/// A module per top-level folder, plus an index module for the remaining
+/// assets which requires the others.
+struct SplitModules {
+    index: Expression,
+    folders: BTreeMap<String, Expression>,
+}
+
+fn generate_split_modules(
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<SplitModules> {
+    let mut root_assets = BTreeMap::new();
+    let mut folder_assets: BTreeMap<String, (String, BTreeMap<String, String>)> = BTreeMap::new();
+
+    for (path, asset_id) in assets {
+        let relative = path
+            .strip_prefix(strip_dir)
+            .context("Failed to strip directory prefix")?;
+        let trimmed = relative.trim_start_matches('/');
+
+        match trimmed.split_once('/') {
+            Some((folder, _)) => {
+                if folder == "init" || folder == "index" {
+                    bail!("A top-level folder cannot be named {folder} when splitting generated code by folder");
+                }
+
+                let separator = &relative[..relative.len() - trimmed.len()];
+                let (_, entries) = folder_assets.entry(folder.to_string()).or_insert_with(|| {
+                    (format!("{strip_dir}{separator}{folder}/"), BTreeMap::new())
+                });
+                entries.insert(path.clone(), asset_id.clone());
+            }
+            None => {
+                root_assets.insert(path.clone(), asset_id.clone());
+            }
+        }
+    }
+
+    let mut folders = BTreeMap::new();
+    for (folder, (folder_strip_dir, entries)) in &folder_assets {
+        let mut expression = generate_table(entries, folder_strip_dir, options)?;
+        sort::sort_expression(&mut expression, options.sort);
+        folders.insert(folder.clone(), expression);
+    }
+
+    let mut index = generate_table(&root_assets, strip_dir, options)?;
+    if let Expression::Table(table) = &mut index {
+        for folder in folders.keys() {
+            let key = keys::convert_key(folder, options.key_style);
+            let collides = table
+                .expressions
+                .iter()
+                .any(|(existing, _)| matches!(existing, Expression::String(existing) if *existing == key));
+            if collides {
+                bail!("The folder {folder} would generate the key {key}, which is already used");
+            }
+
+            table
+                .expressions
+                .push((key.into(), Expression::Require(folder.clone())));
+        }
+    }
+    if options.lookup {
+        add_lookup(&mut index, assets, strip_dir, options)?;
+    }
+    add_meta(&mut index, options);
+    sort::sort_expression(&mut index, options.sort);
+    preload::add_preload(&mut index, assets, strip_dir, options)?;
+
+    Ok(SplitModules { index, folders })
+}
+
+fn luau_target(options: &CodegenOptions) -> AstTarget {
+    if options.luau_types {
+        AstTarget::TypedLuau
+    } else {
+        AstTarget::Luau
+    }
+}
+
+fn typescript_target(options: &CodegenOptions) -> AstTarget {
+    let output_dir = options.output_name.clone();
+
+    match options.typescript_mode {
+        TypescriptMode::Declaration => AstTarget::Typescript { output_dir },
+        TypescriptMode::Source => AstTarget::TypescriptSource { output_dir },
+    }
+}
+
+fn typescript_extension(options: &CodegenOptions) -> &'static str {
+    match options.typescript_mode {
+        TypescriptMode::Declaration => "d.ts",
+        TypescriptMode::Source => "ts",
     }
 }
 
-pub fn generate_ts(
+/// Generates every file enabled by the options, keyed by their path relative
+/// to the write directory.
+pub fn generate_files(
     assets: &BTreeMap<String, String>,
     strip_dir: &str,
-    output_dir: &str,
-    style: &CodegenStyle,
-    strip_extension: bool,
+    options: &CodegenOptions,
+) -> anyhow::Result<BTreeMap<PathBuf, String>> {
+    let mut files = BTreeMap::new();
+    let output_name = &options.output_name;
+
+    if options.split_by_folder {
+        let modules = generate_split_modules(assets, strip_dir, options)?;
+        let module_dir = PathBuf::from(output_name);
+
+        if options.luau {
+            files.insert(
+                module_dir.join("init.luau"),
+                generate_code(&modules.index, luau_target(options), options)?,
+            );
+            for (folder, expression) in &modules.folders {
+                files.insert(
+                    module_dir.join(format!("{folder}.luau")),
+                    generate_code(expression, luau_target(options), options)?,
+                );
+            }
+        }
+
+        if options.typescript {
+            if options.typescript_mode == TypescriptMode::Source {
+                bail!("Typescript source modules cannot be split by folder");
+            }
+
+            files.insert(
+                module_dir.join("index.d.ts"),
+                generate_code(&modules.index, typescript_target(options), options)?,
+            );
+            for (folder, expression) in &modules.folders {
+                files.insert(
+                    module_dir.join(format!("{folder}.d.ts")),
+                    generate_code(expression, typescript_target(options), options)?,
+                );
+            }
+        }
+    } else {
+        let expression = generate_expression(assets, strip_dir, options)?;
+
+        if options.luau {
+            files.insert(
+                PathBuf::from(format!("{output_name}.luau")),
+                generate_code(&expression, luau_target(options), options)?,
+            );
+        }
+
+        if options.typescript {
+            files.insert(
+                PathBuf::from(format!("{output_name}.{}", typescript_extension(options))),
+                generate_code(&expression, typescript_target(options), options)?,
+            );
+        }
+    }
+
+    if options.luau && options.init_shim {
+        files.insert(PathBuf::from("init.luau"), generate_init_shim(options)?);
+    }
+
+    if options.json {
+        let mut expression = generate_expression(assets, strip_dir, options)?;
+        add_json_header(&mut expression, options);
+
+        files.insert(
+            PathBuf::from(format!("{output_name}.json")),
+            generate_code(&expression, AstTarget::Json, options)?,
+        );
+    }
+
+    if !options.bundles.is_empty() {
+        let mut expression = bundles::generate_table(assets, strip_dir, options)?;
+        sort::sort_expression(&mut expression, options.sort);
+        insert_companion_files(
+            &mut files,
+            &format!("{output_name}_bundles"),
+            &expression,
+            options,
+        )?;
+    }
+
+    if options.preload_list {
+        let expression = preload::generate_list(assets, options)?;
+        insert_companion_files(
+            &mut files,
+            &format!("{output_name}_preload"),
+            &expression,
+            options,
+        )?;
+    }
+
+    Ok(files)
+}
+
+/// Adds a file for every enabled target with the given name, for modules
+/// generated alongside the main one.
+fn insert_companion_files(
+    files: &mut BTreeMap<PathBuf, String>,
+    name: &str,
+    expression: &Expression,
+    options: &CodegenOptions,
+) -> anyhow::Result<()> {
+    if options.luau {
+        files.insert(
+            PathBuf::from(format!("{name}.luau")),
+            generate_code(expression, AstTarget::Luau, options)?,
+        );
+    }
+
+    if options.typescript {
+        let output_dir = name.to_string();
+        let target = match options.typescript_mode {
+            TypescriptMode::Declaration => AstTarget::Typescript { output_dir },
+            TypescriptMode::Source => AstTarget::TypescriptSource { output_dir },
+        };
+        files.insert(
+            PathBuf::from(format!("{name}.{}", typescript_extension(options))),
+            generate_code(expression, target, options)?,
+        );
+    }
+
+    if options.json {
+        files.insert(
+            PathBuf::from(format!("{name}.json")),
+            generate_code(expression, AstTarget::Json, options)?,
+        );
+    }
+
+    Ok(())
+}
+
+fn generate_code(
+    expression: &Expression,
+    target: AstTarget,
+    options: &CodegenOptions,
 ) -> anyhow::Result<String> {
-    match style {
-        CodegenStyle::Flat => flat::generate_ts(assets, strip_dir, output_dir, strip_extension),
-        CodegenStyle::Nested => nested::generate_ts(assets, strip_dir, output_dir, strip_extension),
+    let formatting = options.formatting();
+
+    let comment = match target {
+        AstTarget::Luau | AstTarget::TypedLuau | AstTarget::LuauType => Some("--"),
+        AstTarget::Typescript { .. } | AstTarget::TypescriptSource { .. } => Some("//"),
+        AstTarget::Json => None,
+    };
+
+    let mut buffer = String::new();
+
+    if let (Some(header), Some(comment)) = (&options.header, comment) {
+        write_comment(&mut buffer, header, comment)?;
+        writeln!(buffer)?;
     }
+
+    write!(
+        buffer,
+        "{}",
+        ReturnStatement(expression, target, &formatting)
+    )?;
+    Ok(buffer)
 }
 
-fn generate_code(expression: Expression, target: AstTarget) -> anyhow::Result<String> {
+fn write_comment(buffer: &mut String, text: &str, comment: &str) -> anyhow::Result<()> {
+    for line in text.lines() {
+        if line.is_empty() {
+            writeln!(buffer, "{comment}")?;
+        } else {
+            writeln!(buffer, "{comment} {line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Generates an `init.luau` for the write directory that re-exports the
+/// generated module, so the folder can be required without knowing its name.
+fn generate_init_shim(options: &CodegenOptions) -> anyhow::Result<String> {
+    let mut note = format!("Re-exports the generated `{}` module.", options.output_name);
+    if let Some(rojo_path) = &options.rojo_path {
+        write!(note, "\nExpected at {rojo_path} in the DataModel.")?;
+    }
+
     let mut buffer = String::new();
-    write!(buffer, "{}", ReturnStatement(expression, target))?;
+    if let Some(header) = &options.header {
+        write_comment(&mut buffer, header, "--")?;
+        writeln!(buffer, "--")?;
+    }
+    write_comment(&mut buffer, &note, "--")?;
+    writeln!(buffer)?;
+
+    let module = Expression::Require(options.output_name.clone());
+    write!(
+        buffer,
+        "{}",
+        ReturnStatement(&module, AstTarget::Luau, &options.formatting())
+    )?;
     Ok(buffer)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
+    use super::{generate_node_tree, CodegenMeta, CodegenOptions};
+    use crate::asset::AssetType;
+    use crate::commands::sync::config::{
+        CodegenKeyStyle, CodegenSort, CodegenStyle, SpriteRect, TypescriptMode,
+    };
+    use crate::util::flipbook::Flipbook;
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        path::Path,
+    };
+
+    fn options(style: CodegenStyle, strip_extension: bool) -> CodegenOptions {
+        CodegenOptions {
+            output_name: "assets".to_string(),
+            luau: true,
+            typescript: true,
+            typescript_mode: TypescriptMode::Declaration,
+            json: true,
+            split_by_folder: false,
+            style,
+            sort: CodegenSort::Bytewise,
+            key_style: CodegenKeyStyle::Preserve,
+            strip_extension,
+            luau_types: false,
+            numeric_ids: false,
+            include_dimensions: false,
+            content: false,
+            lookup: false,
+            header: None,
+            init_shim: false,
+            rojo_path: None,
+            indent: "\t".to_string(),
+            trailing_commas: true,
+            source_comments: false,
+            variant_separator: None,
+            frame_sequences: false,
+            frame_rate: None,
+            dimensions: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+            asset_types: BTreeMap::new(),
+            keys: None,
+            bundles: BTreeMap::new(),
+            preload_order: Vec::new(),
+            preload_list: false,
+            preload_types: Vec::new(),
+            rects: BTreeMap::new(),
+            slice_center_globs: BTreeMap::new(),
+            slice_centers: BTreeMap::new(),
+            flipbooks: BTreeMap::new(),
+            premultiplied_globs: Vec::new(),
+            premultiplied: BTreeSet::new(),
+            sizes: BTreeMap::new(),
+            meta: None,
+        }
+    }
+
+    fn generate(assets: &BTreeMap<String, String>, options: &CodegenOptions, file: &str) -> String {
+        super::generate_files(assets, "assets", options)
+            .unwrap()
+            .remove(Path::new(file))
+            .unwrap()
+    }
 
     fn test_assets() -> BTreeMap<String, String> {
         let mut entries = BTreeMap::new();
@@ -58,58 +903,741 @@ mod tests {
     fn generate_luau() {
         let lockfile = test_assets();
 
-        let lua = super::flat::generate_luau(&lockfile, "assets", false).unwrap();
+        let lua = generate(
+            &lockfile,
+            &options(CodegenStyle::Flat, false),
+            "assets.luau",
+        );
         assert_eq!(lua, "return {\n\t[\"/bar/baz.png\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\t[\"/foo.png\"] = \"rbxassetid://1\",\n}\n");
 
-        let lua = super::flat::generate_luau(&lockfile, "assets", true).unwrap();
+        let lua = generate(&lockfile, &options(CodegenStyle::Flat, true), "assets.luau");
         assert_eq!(
             lua,
             "return {\n\t[\"/bar/baz\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\t[\"/foo\"] = \"rbxassetid://1\",\n}\n"
         );
     }
 
+    #[test]
+    fn generate_reserved_keys() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/end/nil.png".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+        lockfile.insert(
+            "assets/ui/function.png".to_string(),
+            "rbxassetid://2".to_string(),
+        );
+        let options = options(CodegenStyle::Nested, true);
+
+        let lua = generate(&lockfile, &options, "assets.luau");
+        assert_eq!(lua, "return {\n\t[\"end\"] = {\n\t\t[\"nil\"] = \"rbxassetid://1\",\n\t},\n\tui = {\n\t\t[\"function\"] = \"rbxassetid://2\",\n\t},\n}\n");
+
+        let ts = generate(&lockfile, &options, "assets.d.ts");
+        assert_eq!(ts, "declare const assets: {\n\tend: {\n\t\tnil: \"rbxassetid://1\";\n\t};\n\tui: {\n\t\tfunction: \"rbxassetid://2\";\n\t};\n};\nexport = assets;\n");
+    }
+
     #[test]
     fn generate_ts() {
         let lockfile = test_assets();
 
-        let ts = super::flat::generate_ts(&lockfile, "assets", "assets", false).unwrap();
+        let ts = generate(
+            &lockfile,
+            &options(CodegenStyle::Flat, false),
+            "assets.d.ts",
+        );
         assert_eq!(ts, "declare const assets: {\n\t\"/bar/baz.png\": \"rbxasset://.asphalt/bar/baz.png\";\n\t\"/foo.png\": \"rbxassetid://1\";\n};\nexport = assets;\n");
 
-        let ts = super::flat::generate_ts(&lockfile, "assets", "assets", true).unwrap();
+        let ts = generate(&lockfile, &options(CodegenStyle::Flat, true), "assets.d.ts");
         assert_eq!(ts, "declare const assets: {\n\t\"/bar/baz\": \"rbxasset://.asphalt/bar/baz.png\";\n\t\"/foo\": \"rbxassetid://1\";\n};\nexport = assets;\n");
     }
 
+    #[test]
+    fn generate_luau_by_type() {
+        let mut lockfile = test_assets();
+        lockfile.insert("assets/hit.mp3".to_string(), "rbxassetid://2".to_string());
+        lockfile.insert("assets/walk.rbxm".to_string(), "rbxassetid://3".to_string());
+
+        let mut options = options(CodegenStyle::ByType, true);
+        options
+            .asset_types
+            .insert("assets/walk.rbxm".to_string(), AssetType::Model);
+
+        let lua = generate(&lockfile, &options, "assets.luau");
+        assert_eq!(lua, "return {\n\taudio = {\n\t\t[\"/hit\"] = \"rbxassetid://2\",\n\t},\n\timages = {\n\t\t[\"/bar/baz\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\t\t[\"/foo\"] = \"rbxassetid://1\",\n\t},\n\tmodels = {\n\t\t[\"/walk\"] = \"rbxassetid://3\",\n\t},\n}\n");
+    }
+
+    #[test]
+    fn generate_luau_keys() {
+        let lockfile = test_assets();
+        let mut keys = BTreeMap::new();
+        keys.insert("assets/foo.png".to_string(), "Foo".to_string());
+
+        let options = CodegenOptions {
+            keys: Some(keys),
+            ..options(CodegenStyle::Nested, false)
+        };
+
+        let lua = generate(&lockfile, &options, "assets.luau");
+        assert_eq!(lua, "return {\n\tFoo = \"rbxassetid://1\",\n}\n");
+    }
+
+    #[test]
+    fn generate_bundles() {
+        let lockfile = test_assets();
+        let mut options = options(CodegenStyle::Flat, false);
+        options
+            .bundles
+            .insert("ui".to_string(), vec!["assets/bar/**".to_string()]);
+        options.sizes.insert("assets/bar/baz.png".to_string(), 512);
+        options.sizes.insert("assets/foo.png".to_string(), 256);
+
+        let lua = generate(&lockfile, &options, "assets_bundles.luau");
+        assert_eq!(lua, "return {\n\tui = {\n\t\tassets = {\n\t\t\t[\"bar/baz.png\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\t\t},\n\t\tbytes = 512,\n\t},\n}\n");
+    }
+
+    #[test]
+    fn generate_preload_order() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            preload_order: vec!["assets/foo.png".to_string(), "assets/bar/**".to_string()],
+            ..options(CodegenStyle::Flat, true)
+        };
+
+        let lua = generate(&lockfile, &options, "assets.luau");
+        assert_eq!(lua, "return {\n\t[\"/foo\"] = \"rbxassetid://1\",\n\t[\"/bar/baz\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\tpreload = {\n\t\t\"rbxassetid://1\",\n\t\t\"rbxasset://.asphalt/bar/baz.png\",\n\t},\n}\n");
+
+        let ts = generate(&lockfile, &options, "assets.d.ts");
+        assert_eq!(ts, "declare const assets: {\n\t\"/foo\": \"rbxassetid://1\";\n\t\"/bar/baz\": \"rbxasset://.asphalt/bar/baz.png\";\n\tpreload: string[];\n};\nexport = assets;\n");
+    }
+
+    #[test]
+    fn generate_preload_list() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            preload_list: true,
+            preload_order: vec!["assets/bar/**".to_string()],
+            ..options(CodegenStyle::Flat, false)
+        };
+
+        let lua = generate(&lockfile, &options, "assets_preload.luau");
+        assert_eq!(
+            lua,
+            "return {\n\t\"rbxasset://.asphalt/bar/baz.png\",\n\t\"rbxassetid://1\",\n}\n"
+        );
+
+        let mut options = CodegenOptions {
+            preload_types: vec![AssetType::Audio],
+            ..options
+        };
+        options
+            .asset_types
+            .insert("assets/foo.png".to_string(), AssetType::Audio);
+        let lua = generate(&lockfile, &options, "assets_preload.luau");
+        assert_eq!(lua, "return {\n\t\"rbxassetid://1\",\n}\n");
+    }
+
+    #[test]
+    fn generate_nested_node_tree() {
+        let lockfile = test_assets();
+        let mut options = options(CodegenStyle::Nested, true);
+        options
+            .asset_types
+            .insert("assets/foo.png".to_string(), AssetType::Decal);
+
+        let tree = generate_node_tree(&lockfile, "assets", &options).unwrap();
+        assert_eq!(serde_json::to_string(&tree).unwrap(), "{\"kind\":\"table\",\"children\":{\"bar\":{\"kind\":\"table\",\"children\":{\"baz\":{\"kind\":\"asset\",\"path\":\"assets/bar/baz.png\",\"uri\":\"rbxasset://.asphalt/bar/baz.png\"}}},\"foo\":{\"kind\":\"asset\",\"path\":\"assets/foo.png\",\"uri\":\"rbxassetid://1\",\"asset_type\":\"decal\"}}}");
+    }
+
     #[test]
     fn generate_luau_nested() {
         let lockfile = test_assets();
 
-        let lua = super::nested::generate_luau(&lockfile, "assets", false).unwrap();
+        let lua = generate(
+            &lockfile,
+            &options(CodegenStyle::Nested, false),
+            "assets.luau",
+        );
         assert_eq!(
             lua,
             "return {\n\tbar = {\n\t\t[\"baz.png\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\t[\"foo.png\"] = \"rbxassetid://1\",\n}\n"
         );
 
-        let lua = super::nested::generate_luau(&lockfile, "assets", true).unwrap();
+        let lua = generate(
+            &lockfile,
+            &options(CodegenStyle::Nested, true),
+            "assets.luau",
+        );
         assert_eq!(
             lua,
             "return {\n\tbar = {\n\t\tbaz = \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo = \"rbxassetid://1\",\n}\n"
         );
     }
 
+    #[test]
+    fn generate_luau_typed() {
+        let lockfile = test_assets();
+
+        let options = CodegenOptions {
+            luau_types: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        let lua = generate(&lockfile, &options, "assets.luau");
+        assert_eq!(
+            lua,
+            "local assets: {\n\tbar: {\n\t\tbaz: \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo: \"rbxassetid://1\",\n} = {\n\tbar = {\n\t\tbaz = \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo = \"rbxassetid://1\",\n}\n\nexport type Assets = typeof(assets)\n\nreturn assets\n"
+        );
+    }
+
     #[test]
     fn generate_ts_nested() {
         let lockfile = test_assets();
 
-        let ts = super::nested::generate_ts(&lockfile, "assets", "assets", false).unwrap();
+        let ts = generate(
+            &lockfile,
+            &options(CodegenStyle::Nested, false),
+            "assets.d.ts",
+        );
         assert_eq!(
             ts,
             "declare const assets: {\n\tbar: {\n\t\t\"baz.png\": \"rbxasset://.asphalt/bar/baz.png\";\n\t};\n\t\"foo.png\": \"rbxassetid://1\";\n};\nexport = assets;\n"
         );
 
-        let ts = super::nested::generate_ts(&lockfile, "assets", "assets", true).unwrap();
+        let ts = generate(
+            &lockfile,
+            &options(CodegenStyle::Nested, true),
+            "assets.d.ts",
+        );
         assert_eq!(
             ts,
             "declare const assets: {\n\tbar: {\n\t\tbaz: \"rbxasset://.asphalt/bar/baz.png\";\n\t};\n\tfoo: \"rbxassetid://1\";\n};\nexport = assets;\n"
         );
     }
+
+    #[test]
+    fn generate_header() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            header: Some("DO NOT EDIT\n\nGenerated by Asphalt".to_string()),
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert!(generate(&lockfile, &options, "assets.luau")
+            .starts_with("-- DO NOT EDIT\n--\n-- Generated by Asphalt\n\nreturn {\n"));
+        assert!(generate(&lockfile, &options, "assets.d.ts")
+            .starts_with("// DO NOT EDIT\n//\n// Generated by Asphalt\n\ndeclare const assets"));
+        assert!(generate(&lockfile, &options, "assets.json")
+            .starts_with("{\n\t\"$header\": \"DO NOT EDIT\\n\\nGenerated by Asphalt\",\n"));
+    }
+
+    #[test]
+    fn generate_init_shim() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            init_shim: true,
+            rojo_path: Some("ReplicatedStorage/Shared/assets".to_string()),
+            ..options(CodegenStyle::Flat, false)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "init.luau"),
+            "-- Re-exports the generated `assets` module.\n-- Expected at ReplicatedStorage/Shared/assets in the DataModel.\n\nreturn require(script.assets)\n"
+        );
+    }
+
+    #[test]
+    fn generate_lookup() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            lookup: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\tbar = {\n\t\tbaz = \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo = \"rbxassetid://1\",\n\tget = (function()\n\t\tlocal paths = {\n\t\t\t[\"bar/baz\"] = \"rbxasset://.asphalt/bar/baz.png\",\n\t\t\tfoo = \"rbxassetid://1\",\n\t\t}\n\n\t\treturn function(path: string)\n\t\t\treturn paths[path]\n\t\tend\n\tend)(),\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.d.ts"),
+            "declare const assets: {\n\tbar: {\n\t\tbaz: \"rbxasset://.asphalt/bar/baz.png\";\n\t};\n\tfoo: \"rbxassetid://1\";\n\tget: (path: string) => string | undefined;\n};\nexport = assets;\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.json"),
+            "{\n\t\"bar\": {\n\t\t\"baz\": \"rbxasset://.asphalt/bar/baz.png\"\n\t},\n\t\"foo\": \"rbxassetid://1\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_ts_source() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            typescript_mode: TypescriptMode::Source,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.ts"),
+            "const assets = {\n\tbar: {\n\t\tbaz: \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo: \"rbxassetid://1\",\n} as const;\n\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_split_by_folder() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            split_by_folder: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets/init.luau"),
+            "return {\n\tbar = require(script.bar),\n\tfoo = \"rbxassetid://1\",\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets/bar.luau"),
+            "return {\n\tbaz = \"rbxasset://.asphalt/bar/baz.png\",\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets/index.d.ts"),
+            "declare const assets: {\n\tbar: typeof import(\"./bar\");\n\tfoo: \"rbxassetid://1\";\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_content() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            content: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\tbar = {\n\t\tbaz = Content.fromUri(\"rbxasset://.asphalt/bar/baz.png\"),\n\t},\n\tfoo = Content.fromAssetId(1),\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.d.ts"),
+            "declare const assets: {\n\tbar: {\n\t\tbaz: Content;\n\t};\n\tfoo: Content;\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_numeric_ids() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            numeric_ids: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\tbar = {\n\t\tbaz = {\n\t\t\turl = \"rbxasset://.asphalt/bar/baz.png\",\n\t\t},\n\t},\n\tfoo = {\n\t\tid = 1,\n\t\turl = \"rbxassetid://1\",\n\t},\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.d.ts"),
+            "declare const assets: {\n\tbar: {\n\t\tbaz: {\n\t\t\turl: \"rbxasset://.asphalt/bar/baz.png\";\n\t\t};\n\t};\n\tfoo: {\n\t\tid: 1;\n\t\turl: \"rbxassetid://1\";\n\t};\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_dimensions() {
+        let lockfile = test_assets();
+        let mut dimensions = BTreeMap::new();
+        dimensions.insert("assets/foo.png".to_string(), (128, 64));
+
+        let options = CodegenOptions {
+            include_dimensions: true,
+            dimensions,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\tbar = {\n\t\tbaz = \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo = {\n\t\theight = 64,\n\t\turl = \"rbxassetid://1\",\n\t\twidth = 128,\n\t},\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_sprite_rects() {
+        let lockfile = test_assets();
+        let mut rects = BTreeMap::new();
+        rects.insert(
+            "assets/foo.png".to_string(),
+            SpriteRect {
+                x: 32,
+                y: 0,
+                width: 16,
+                height: 16,
+            },
+        );
+
+        let options = CodegenOptions {
+            rects,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\tbar = {\n\t\tbaz = \"rbxasset://.asphalt/bar/baz.png\",\n\t},\n\tfoo = {\n\t\trect = {\n\t\t\theight = 16,\n\t\t\twidth = 16,\n\t\t\tx = 32,\n\t\t\ty = 0,\n\t\t},\n\t\turl = \"rbxassetid://1\",\n\t},\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_slice_centers() {
+        let lockfile = test_assets();
+        let center = SpriteRect {
+            x: 8,
+            y: 8,
+            width: 16,
+            height: 16,
+        };
+
+        let mut options = options(CodegenStyle::Nested, true);
+        options
+            .slice_center_globs
+            .insert("assets/*.png".to_string(), center);
+        options
+            .slice_center_globs
+            .insert("assets/bar/**".to_string(), center);
+        options.match_slice_centers(&lockfile).unwrap();
+
+        assert_eq!(generate(&lockfile, &options, "assets.luau"), "return {\n\tbar = {\n\t\tbaz = {\n\t\t\tslice_center = {\n\t\t\t\theight = 16,\n\t\t\t\twidth = 16,\n\t\t\t\tx = 8,\n\t\t\t\ty = 8,\n\t\t\t},\n\t\t\turl = \"rbxasset://.asphalt/bar/baz.png\",\n\t\t},\n\t},\n\tfoo = {\n\t\tslice_center = {\n\t\t\theight = 16,\n\t\t\twidth = 16,\n\t\t\tx = 8,\n\t\t\ty = 8,\n\t\t},\n\t\turl = \"rbxassetid://1\",\n\t},\n}\n");
+
+        options
+            .slice_center_globs
+            .insert("assets/foo.png".to_string(), SpriteRect { x: 0, ..center });
+        let error = options.match_slice_centers(&lockfile).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Conflicting slice centers (assets/*.png and assets/foo.png) match assets/foo.png"
+        );
+    }
+
+    #[test]
+    fn generate_variants() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/ui/icon@dark.png".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+        lockfile.insert(
+            "assets/ui/icon@light.png".to_string(),
+            "rbxassetid://2".to_string(),
+        );
+        lockfile.insert("assets/logo.png".to_string(), "rbxassetid://3".to_string());
+
+        let nested = CodegenOptions {
+            variant_separator: Some("@".to_string()),
+            typescript: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+        assert_eq!(generate(&lockfile, &nested, "assets.luau"), "return {\n\tlogo = \"rbxassetid://3\",\n\tui = {\n\t\ticon = {\n\t\t\tdark = \"rbxassetid://1\",\n\t\t\tlight = \"rbxassetid://2\",\n\t\t},\n\t},\n}\n");
+        assert_eq!(generate(&lockfile, &nested, "assets.d.ts"), "declare const assets: {\n\tlogo: \"rbxassetid://3\";\n\tui: {\n\t\ticon: {\n\t\t\tdark: \"rbxassetid://1\";\n\t\t\tlight: \"rbxassetid://2\";\n\t\t};\n\t};\n};\nexport = assets;\n");
+
+        let options = CodegenOptions {
+            variant_separator: Some("@".to_string()),
+            ..options(CodegenStyle::Flat, false)
+        };
+        assert_eq!(generate(&lockfile, &options, "assets.luau"), "return {\n\t[\"/logo.png\"] = \"rbxassetid://3\",\n\t[\"/ui/icon.png\"] = {\n\t\tdark = \"rbxassetid://1\",\n\t\tlight = \"rbxassetid://2\",\n\t},\n}\n");
+
+        lockfile.insert(
+            "assets/ui/icon.png".to_string(),
+            "rbxassetid://4".to_string(),
+        );
+        let error = super::generate_files(&lockfile, "assets", &options).unwrap_err();
+        assert_eq!(format!("{error:#}"), "assets/ui/icon.png would generate the key /ui/icon.png, which is already used by its variants");
+    }
+
+    #[test]
+    fn generate_flipbooks() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/spinner.gif".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+
+        let mut flipbooks = CodegenOptions {
+            typescript: true,
+            ..options(CodegenStyle::Flat, true)
+        };
+        flipbooks.flipbooks.insert(
+            "assets/spinner.gif".to_string(),
+            Flipbook {
+                frame_width: 32,
+                frame_height: 32,
+                columns: 4,
+                durations: vec![100, 100, 250],
+            },
+        );
+
+        assert_eq!(
+            generate(&lockfile, &flipbooks, "assets.luau"),
+            "return {\n\t[\"/spinner\"] = {\n\t\tflipbook = {\n\t\t\tcolumns = 4,\n\t\t\tdurations = {\n\t\t\t\t100,\n\t\t\t\t100,\n\t\t\t\t250,\n\t\t\t},\n\t\t\tframe_height = 32,\n\t\t\tframe_width = 32,\n\t\t},\n\t\turl = \"rbxassetid://1\",\n\t},\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &flipbooks, "assets.d.ts"),
+            "declare const assets: {\n\t\"/spinner\": {\n\t\tflipbook: {\n\t\t\tcolumns: 4;\n\t\t\tdurations: number[];\n\t\t\tframe_height: 32;\n\t\t\tframe_width: 32;\n\t\t};\n\t\turl: \"rbxassetid://1\";\n\t};\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_premultiplied() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert("assets/glow.png".to_string(), "rbxassetid://1".to_string());
+        lockfile.insert("assets/icon.png".to_string(), "rbxassetid://2".to_string());
+
+        let mut premultiplied = CodegenOptions {
+            premultiplied_globs: vec!["**/glow.png".to_string()],
+            ..options(CodegenStyle::Flat, true)
+        };
+        premultiplied.match_premultiplied(&lockfile).unwrap();
+
+        assert_eq!(
+            generate(&lockfile, &premultiplied, "assets.luau"),
+            "return {\n\t[\"/glow\"] = {\n\t\talpha = \"premultiplied\",\n\t\turl = \"rbxassetid://1\",\n\t},\n\t[\"/icon\"] = \"rbxassetid://2\",\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &premultiplied, "assets.d.ts"),
+            "declare const assets: {\n\t\"/glow\": {\n\t\talpha: \"premultiplied\";\n\t\turl: \"rbxassetid://1\";\n\t};\n\t\"/icon\": \"rbxassetid://2\";\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_frame_sequences() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/run_10.png".to_string(),
+            "rbxassetid://3".to_string(),
+        );
+        lockfile.insert(
+            "assets/run_02.png".to_string(),
+            "rbxassetid://2".to_string(),
+        );
+        lockfile.insert(
+            "assets/run_01.png".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+        lockfile.insert(
+            "assets/logo_1.png".to_string(),
+            "rbxassetid://4".to_string(),
+        );
+
+        let frames = CodegenOptions {
+            frame_sequences: true,
+            frame_rate: Some(12),
+            typescript: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+        assert_eq!(generate(&lockfile, &frames, "assets.luau"), "return {\n\tlogo_1 = \"rbxassetid://4\",\n\trun = {\n\t\tfps = 12,\n\t\tframes = {\n\t\t\t\"rbxassetid://1\",\n\t\t\t\"rbxassetid://2\",\n\t\t\t\"rbxassetid://3\",\n\t\t},\n\t},\n}\n");
+        assert_eq!(generate(&lockfile, &frames, "assets.d.ts"), "declare const assets: {\n\tlogo_1: \"rbxassetid://4\";\n\trun: {\n\t\tfps: 12;\n\t\tframes: string[];\n\t};\n};\nexport = assets;\n");
+
+        lockfile.insert("assets/run.png".to_string(), "rbxassetid://5".to_string());
+        let error = super::generate_files(&lockfile, "assets", &frames).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "The frames of run would generate a key which is already used"
+        );
+    }
+
+    #[test]
+    fn generate_resolution_variants() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/gear@1x.png".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+        lockfile.insert(
+            "assets/gear@2x.png".to_string(),
+            "rbxassetid://2".to_string(),
+        );
+
+        let variants = CodegenOptions {
+            variant_separator: Some("@".to_string()),
+            typescript: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+        assert_eq!(generate(&lockfile, &variants, "assets.luau"), "return {\n\tgear = {\n\t\t[\"1x\"] = \"rbxassetid://1\",\n\t\t[\"2x\"] = \"rbxassetid://2\",\n\t},\n}\n");
+        assert_eq!(generate(&lockfile, &variants, "assets.d.ts"), "declare const assets: {\n\tgear: {\n\t\t\"1x\": \"rbxassetid://1\";\n\t\t\"2x\": \"rbxassetid://2\";\n\t};\n};\nexport = assets;\n");
+    }
+
+    #[test]
+    fn generate_escaped_keys() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/foo\"bar\\baz\n\u{1}.png".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+        let options = options(CodegenStyle::Flat, true);
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\t[\"/foo\\\"bar\\\\baz\\n\\u{1}\"] = \"rbxassetid://1\",\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.json"),
+            "{\n\t\"/foo\\\"bar\\\\baz\\n\\u0001\": \"rbxassetid://1\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_source_comments() {
+        let lockfile = test_assets();
+        let mut options = CodegenOptions {
+            source_comments: true,
+            ..options(CodegenStyle::Flat, false)
+        };
+        options
+            .hashes
+            .insert("assets/foo.png".to_string(), "abcd1234ef".to_string());
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n\t[\"/bar/baz.png\"] = \"rbxasset://.asphalt/bar/baz.png\", -- assets/bar/baz.png\n\t[\"/foo.png\"] = \"rbxassetid://1\", -- assets/foo.png (hash abcd1234)\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.d.ts"),
+            "declare const assets: {\n\t\"/bar/baz.png\": \"rbxasset://.asphalt/bar/baz.png\"; // assets/bar/baz.png\n\t\"/foo.png\": \"rbxassetid://1\"; // assets/foo.png (hash abcd1234)\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_key_style() {
+        let mut lockfile = BTreeMap::new();
+        lockfile.insert(
+            "assets/ui-icons/close-button.png".to_string(),
+            "rbxassetid://1".to_string(),
+        );
+        lockfile.insert(
+            "assets/ui-icons/OpenButton.png".to_string(),
+            "rbxassetid://2".to_string(),
+        );
+
+        let camel_case = CodegenOptions {
+            key_style: CodegenKeyStyle::CamelCase,
+            ..options(CodegenStyle::Nested, true)
+        };
+        assert_eq!(
+            generate(&lockfile, &camel_case, "assets.luau"),
+            "return {\n\tuiIcons = {\n\t\tcloseButton = \"rbxassetid://1\",\n\t\topenButton = \"rbxassetid://2\",\n\t},\n}\n"
+        );
+
+        let snake_case = CodegenOptions {
+            key_style: CodegenKeyStyle::SnakeCase,
+            ..options(CodegenStyle::Flat, false)
+        };
+        assert_eq!(
+            generate(&lockfile, &snake_case, "assets.luau"),
+            "return {\n\t[\"/ui_icons/close_button.png\"] = \"rbxassetid://1\",\n\t[\"/ui_icons/open_button.png\"] = \"rbxassetid://2\",\n}\n"
+        );
+
+        lockfile.insert(
+            "assets/ui-icons/close_button.png".to_string(),
+            "rbxassetid://3".to_string(),
+        );
+        assert!(super::generate_files(&lockfile, "assets", &snake_case).is_err());
+    }
+
+    #[test]
+    fn generate_key_collisions() {
+        let mut lockfile = test_assets();
+        lockfile.insert("assets/foo.jpg".to_string(), "rbxassetid://2".to_string());
+
+        for style in [CodegenStyle::Flat, CodegenStyle::Nested] {
+            let error =
+                super::generate_files(&lockfile, "assets", &options(style, true)).unwrap_err();
+            assert!(
+                format!("{error:#}").contains("assets/foo.jpg and assets/foo.png"),
+                "{error:#}"
+            );
+        }
+
+        lockfile.remove("assets/foo.jpg");
+        lockfile.insert("assets/bar.png".to_string(), "rbxassetid://3".to_string());
+        let error =
+            super::generate_files(&lockfile, "assets", &options(CodegenStyle::Nested, true))
+                .unwrap_err();
+        assert!(
+            format!("{error:#}").contains("used by a folder"),
+            "{error:#}"
+        );
+    }
+
+    #[test]
+    fn generate_formatting() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            indent: "  ".to_string(),
+            trailing_commas: false,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        assert_eq!(
+            generate(&lockfile, &options, "assets.luau"),
+            "return {\n  bar = {\n    baz = \"rbxasset://.asphalt/bar/baz.png\"\n  },\n  foo = \"rbxassetid://1\"\n}\n"
+        );
+        assert_eq!(
+            generate(&lockfile, &options, "assets.d.ts"),
+            "declare const assets: {\n  bar: {\n    baz: \"rbxasset://.asphalt/bar/baz.png\";\n  };\n  foo: \"rbxassetid://1\";\n};\nexport = assets;\n"
+        );
+    }
+
+    #[test]
+    fn generate_luau_natural_sort() {
+        let mut assets = BTreeMap::new();
+        for frame in ["frame1", "frame10", "frame2", "frame02b"] {
+            assets.insert(format!("assets/{frame}.png"), "rbxassetid://1".to_string());
+        }
+
+        let options = CodegenOptions {
+            sort: CodegenSort::Natural,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        let lua = generate(&assets, &options, "assets.luau");
+        assert_eq!(
+            lua,
+            "return {\n\tframe1 = \"rbxassetid://1\",\n\tframe2 = \"rbxassetid://1\",\n\tframe02b = \"rbxassetid://1\",\n\tframe10 = \"rbxassetid://1\",\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_json() {
+        let lockfile = test_assets();
+
+        let json = generate(
+            &lockfile,
+            &options(CodegenStyle::Flat, false),
+            "assets.json",
+        );
+        assert_eq!(json, "{\n\t\"/bar/baz.png\": \"rbxasset://.asphalt/bar/baz.png\",\n\t\"/foo.png\": \"rbxassetid://1\"\n}\n");
+
+        let json = generate(
+            &lockfile,
+            &options(CodegenStyle::Nested, true),
+            "assets.json",
+        );
+        assert_eq!(
+            json,
+            "{\n\t\"bar\": {\n\t\t\"baz\": \"rbxasset://.asphalt/bar/baz.png\"\n\t},\n\t\"foo\": \"rbxassetid://1\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn generate_luau_meta() {
+        let lockfile = test_assets();
+        let options = CodegenOptions {
+            meta: Some(CodegenMeta {
+                synced_at: 1700000000,
+                version: "1.0.0".to_string(),
+                asset_count: 2,
+            }),
+            luau_types: true,
+            ..options(CodegenStyle::Nested, true)
+        };
+
+        let lua = generate(&lockfile, &options, "assets.luau");
+        assert!(lua.contains("\t__meta: {\n\t\tasset_count: number,\n\t\tsynced_at: number,\n\t\tversion: \"1.0.0\",\n\t},"));
+        assert!(lua.contains("\t__meta = {\n\t\tasset_count = 2,\n\t\tsynced_at = 1700000000,\n\t\tversion = \"1.0.0\",\n\t},"));
+    }
 }
@@ -1,6 +1,5 @@
 use self::types::NestedTable;
-use super::ast::{AstTarget, Expression};
-use super::generate_code;
+use super::ast::Expression;
 use anyhow::{bail, Context};
 use std::collections::BTreeMap;
 use std::path::PathBuf;
@@ -25,21 +24,22 @@ fn build_table(entry: &NestedTable) -> Expression {
                 .map(|(component, entry)| (component.into(), build_table(entry)))
                 .collect(),
         ),
-        NestedTable::Asset(asset_id) => Expression::String(asset_id.to_string()),
+        NestedTable::Asset(file_path) => Expression::String(file_path.to_string()),
     }
 }
 
 /**
  * Creates expressions based on a map of assets and builds a table for code generation.
+ * Assets are left as their file paths, to be resolved once the table is built.
 */
-fn generate_expressions(
+pub fn generate_table(
     assets: &BTreeMap<String, String>,
     strip_dir: &str,
     strip_extension: bool,
 ) -> anyhow::Result<Expression> {
     let mut root: BTreeMap<String, NestedTable<'_>> = BTreeMap::new();
 
-    for (file_path, asset_id) in assets.iter() {
+    for file_path in assets.keys() {
         let mut components = vec![];
         let full_path = if strip_extension {
             Path::new(file_path).with_extension("")
@@ -60,10 +60,8 @@ fn generate_expressions(
                             .context("Failed to resolve path component")?,
                     )
                 }
-                PathComponent::ParentDir => {
-                    if components.pop().is_none() {
-                        bail!("Failed to resolve parent directory")
-                    }
+                PathComponent::ParentDir if components.pop().is_none() => {
+                    bail!("Failed to resolve parent directory")
                 }
                 _ => {}
             }
@@ -73,46 +71,31 @@ fn generate_expressions(
         for (index, &component) in components.iter().enumerate() {
             // last component is assumed to be a file.
             if index == components.len() - 1 {
-                if current_directory.get_mut(component).is_none() {
-                    current_directory.insert(component.to_owned(), NestedTable::Asset(asset_id));
-                };
-            } else if let NestedTable::Folder(entries) = current_directory
-                .entry(component.to_owned())
-                .or_insert_with(|| NestedTable::Folder(BTreeMap::new()))
-            {
-                current_directory = entries;
+                match current_directory.get(component) {
+                    Some(NestedTable::Asset(other)) => {
+                        bail!("{other} and {file_path} would both generate the key {component}")
+                    }
+                    Some(NestedTable::Folder(_)) => {
+                        bail!("{file_path} would generate the key {component}, which is already used by a folder")
+                    }
+                    None => {
+                        current_directory
+                            .insert(component.to_owned(), NestedTable::Asset(file_path));
+                    }
+                }
             } else {
-                unreachable!()
+                match current_directory
+                    .entry(component.to_owned())
+                    .or_insert_with(|| NestedTable::Folder(BTreeMap::new()))
+                {
+                    NestedTable::Folder(entries) => current_directory = entries,
+                    NestedTable::Asset(other) => {
+                        bail!("{other} would generate the key {component}, which is already used by a folder")
+                    }
+                }
             }
         }
     }
 
     Ok(build_table(&NestedTable::Folder(root)))
 }
-
-pub fn generate_luau(
-    assets: &BTreeMap<String, String>,
-    strip_dir: &str,
-    strip_extension: bool,
-) -> anyhow::Result<String> {
-    generate_code(
-        generate_expressions(assets, strip_dir, strip_extension)
-            .context("Failed to generate nested table")?,
-        AstTarget::Luau,
-    )
-}
-
-pub fn generate_ts(
-    assets: &BTreeMap<String, String>,
-    strip_dir: &str,
-    output_dir: &str,
-    strip_extension: bool,
-) -> anyhow::Result<String> {
-    generate_code(
-        generate_expressions(assets, strip_dir, strip_extension)
-            .context("Failed to generate nested table")?,
-        AstTarget::Typescript {
-            output_dir: output_dir.to_owned(),
-        },
-    )
-}
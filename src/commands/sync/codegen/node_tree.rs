@@ -0,0 +1,74 @@
+//! The keys generated code would have, with what each asset resolved to, for
+//! tools that generate their own code from Asphalt's mapping.
+
+use super::{ast::Expression, sort::key_string, CodegenOptions};
+use crate::asset::AssetType;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CodegenNode {
+    Table {
+        children: BTreeMap<String, CodegenNode>,
+    },
+    List {
+        items: Vec<CodegenNode>,
+    },
+    Asset {
+        path: String,
+        uri: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        asset_type: Option<AssetType>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        width: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        height: Option<u32>,
+    },
+}
+
+impl CodegenNode {
+    /// Converts a table whose values are still the paths of assets.
+    pub fn from_expression(
+        expression: &Expression,
+        assets: &BTreeMap<String, String>,
+        options: &CodegenOptions,
+    ) -> Option<Self> {
+        match expression {
+            Expression::String(path) => {
+                let uri = assets.get(path)?;
+                let dimensions = options.dimensions.get(path);
+
+                Some(Self::Asset {
+                    path: path.clone(),
+                    uri: uri.clone(),
+                    asset_type: options.asset_types.get(path).copied(),
+                    hash: options.hashes.get(path).cloned(),
+                    width: dimensions.map(|(width, _)| *width),
+                    height: dimensions.map(|(_, height)| *height),
+                })
+            }
+            Expression::Table(table) => Some(Self::Table {
+                children: table
+                    .expressions
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        Some((
+                            key_string(key),
+                            Self::from_expression(value, assets, options)?,
+                        ))
+                    })
+                    .collect(),
+            }),
+            Expression::Array(values) => Some(Self::List {
+                items: values
+                    .iter()
+                    .filter_map(|value| Self::from_expression(value, assets, options))
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::{ast::Expression, by_type, flat, keys, CodegenOptions};
+use crate::commands::sync::config::CodegenStyle;
+use anyhow::{bail, Context};
+use globset::Glob;
+use std::collections::BTreeMap;
+
+/// The paths of assets matching the preload globs, in the order of the first
+/// glob each matches.
+fn preload_paths<'a>(
+    assets: &'a BTreeMap<String, String>,
+    options: &CodegenOptions,
+) -> anyhow::Result<Vec<&'a String>> {
+    let mut paths = Vec::new();
+
+    for glob in &options.preload_order {
+        let matcher = Glob::new(glob)
+            .with_context(|| format!("Invalid glob {glob} in preload order"))?
+            .compile_matcher();
+
+        for path in assets.keys() {
+            if matcher.is_match(path) && !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Moves assets to preload to the start of a flat table, in preload order,
+/// keeping the rest where they were.
+fn reorder_flat(
+    expression: &mut Expression,
+    paths: &[&String],
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<()> {
+    let Expression::Table(table) = expression else {
+        return Ok(());
+    };
+
+    let mut keyed = flat::generate_table(assets, strip_dir, options.strip_extension)?;
+    keys::apply_key_style(&mut keyed, options.key_style)?;
+    let Expression::Table(keyed) = keyed else {
+        return Ok(());
+    };
+
+    let mut ranks = BTreeMap::new();
+    for (key, path) in &keyed.expressions {
+        if let (Expression::String(key), Expression::String(path)) = (key, path) {
+            if let Some(rank) = paths.iter().position(|preload| *preload == path) {
+                ranks.insert(key.clone(), rank);
+            }
+        }
+    }
+
+    table.expressions.sort_by_key(|(key, _)| match key {
+        Expression::String(key) => ranks.get(key).copied().unwrap_or(usize::MAX),
+        _ => usize::MAX,
+    });
+
+    Ok(())
+}
+
+/// Adds a `preload` array with the URIs of assets matching the preload globs,
+/// in order, and moves those assets to the start of flat output.
+pub fn add_preload(
+    expression: &mut Expression,
+    assets: &BTreeMap<String, String>,
+    strip_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<()> {
+    if options.preload_order.is_empty() {
+        return Ok(());
+    }
+
+    let paths = preload_paths(assets, options)?;
+
+    if options.style == CodegenStyle::Flat && options.keys.is_none() {
+        reorder_flat(expression, &paths, assets, strip_dir, options)?;
+    }
+
+    let Expression::Table(table) = expression else {
+        return Ok(());
+    };
+
+    let taken = table
+        .expressions
+        .iter()
+        .any(|(key, _)| matches!(key, Expression::String(key) if key == "preload"));
+    if taken {
+        bail!("An asset would generate the key preload, which is used by the preload order");
+    }
+
+    let uris = paths
+        .iter()
+        .map(|path| Expression::String(assets[*path].clone()))
+        .collect();
+    table
+        .expressions
+        .push(("preload".into(), Expression::Array(uris)));
+
+    Ok(())
+}
+
+/// Builds an array of the URIs of every asset of the preload types, or of
+/// every asset if there are none, with assets in the preload order first.
+pub fn generate_list(
+    assets: &BTreeMap<String, String>,
+    options: &CodegenOptions,
+) -> anyhow::Result<Expression> {
+    let mut paths = preload_paths(assets, options)?;
+    for path in assets.keys() {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    let uris = paths
+        .into_iter()
+        .filter(|path| {
+            options.preload_types.is_empty()
+                || by_type::asset_type(path, &options.asset_types)
+                    .is_some_and(|asset_type| options.preload_types.contains(&asset_type))
+        })
+        .map(|path| Expression::String(assets[path].clone()))
+        .collect();
+
+    Ok(Expression::Array(uris))
+}
@@ -0,0 +1,116 @@
+use std::cmp::Ordering;
+
+use super::ast::Expression;
+use crate::commands::sync::config::CodegenSort;
+
+/// Compares strings so that runs of digits are ordered by their numeric value,
+/// meaning `frame2` comes before `frame10`.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                let a_digits = take_digits(&mut a_chars);
+                let b_digits = take_digits(&mut b_chars);
+
+                let a_number = a_digits.trim_start_matches('0');
+                let b_number = b_digits.trim_start_matches('0');
+
+                let ordering = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(b_number));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(a_char), Some(b_char)) => {
+                let ordering = a_char.cmp(b_char);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(char) = chars.next_if(|char| char.is_ascii_digit()) {
+        digits.push(char);
+    }
+    digits
+}
+
+pub(super) fn key_string(key: &Expression) -> String {
+    match key {
+        Expression::String(value) => value.clone(),
+        Expression::Number(value) => value.to_string(),
+        Expression::Table(_)
+        | Expression::Require(_)
+        | Expression::Content(_)
+        | Expression::Lookup(_)
+        | Expression::Array(_)
+        | Expression::Commented(..) => String::new(),
+    }
+}
+
+/// Recursively sorts the keys of every table in an expression.
+pub fn sort_expression(expression: &mut Expression, sort: CodegenSort) {
+    let Expression::Table(table) = expression else {
+        return;
+    };
+
+    table.expressions.sort_by(|(a, _), (b, _)| {
+        let (a, b) = (key_string(a), key_string(b));
+        match sort {
+            CodegenSort::Bytewise => a.cmp(&b),
+            CodegenSort::Natural => natural_cmp(&a, &b),
+        }
+    });
+
+    for (_, value) in &mut table.expressions {
+        sort_expression(value, sort);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_numbers_by_value() {
+        assert_eq!(natural_cmp("a2", "a10"), Ordering::Less);
+        assert_eq!(natural_cmp("a10", "a2"), Ordering::Greater);
+        assert_eq!(natural_cmp("frame9_b", "frame10_a"), Ordering::Less);
+        assert_eq!(natural_cmp("a2b10", "a2b9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("a10", "a10"), Ordering::Equal);
+    }
+
+    #[test]
+    fn breaks_ties_between_leading_zeros() {
+        assert_eq!(natural_cmp("a007", "a10"), Ordering::Less);
+        assert_eq!(natural_cmp("a0", "a00"), Ordering::Less);
+        // Equal numbers fall back to comparing bytes, so the order is stable.
+        assert_eq!(natural_cmp("a01", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("a1", "a01"), Ordering::Greater);
+        assert_eq!(natural_cmp("a01b", "a1a"), Ordering::Greater);
+    }
+
+    #[test]
+    fn compares_case_bytewise() {
+        assert_eq!(natural_cmp("B", "a"), Ordering::Less);
+        assert_eq!(natural_cmp("Icon2", "icon10"), Ordering::Less);
+        assert_eq!(natural_cmp("icon2", "Icon10"), Ordering::Greater);
+    }
+}
@@ -0,0 +1,82 @@
+use super::ast::Expression;
+use anyhow::bail;
+use std::collections::HashMap;
+
+/// Splits a key like `ui/icon@dark.png` into the key its variants are grouped
+/// under, `ui/icon.png`, and the name of the variant, `dark`.
+fn split_variant(key: &str, separator: &str) -> Option<(String, String)> {
+    let (dir, name) = match key.rsplit_once('/') {
+        Some((dir, name)) => (Some(dir), name),
+        None => (None, key),
+    };
+
+    let (base, rest) = name.split_once(separator)?;
+    let (variant, extension) = match rest.split_once('.') {
+        Some((variant, extension)) => (variant, Some(extension)),
+        None => (rest, None),
+    };
+    if base.is_empty() || variant.is_empty() {
+        return None;
+    }
+
+    let mut grouped = String::new();
+    if let Some(dir) = dir {
+        grouped.push_str(dir);
+        grouped.push('/');
+    }
+    grouped.push_str(base);
+    if let Some(extension) = extension {
+        grouped.push('.');
+        grouped.push_str(extension);
+    }
+
+    Some((grouped, variant.to_string()))
+}
+
+/// Groups assets whose names have a variant after the separator, like
+/// `icon@dark.png` and `icon@light.png`, into a table of their variants,
+/// keyed by the name they share.
+pub fn group_variants(expression: &mut Expression, separator: &str) -> anyhow::Result<()> {
+    let Expression::Table(table) = expression else {
+        return Ok(());
+    };
+
+    let mut expressions: Vec<(Expression, Expression)> = Vec::new();
+    let mut groups: HashMap<String, usize> = HashMap::new();
+
+    for (key, mut value) in std::mem::take(&mut table.expressions) {
+        group_variants(&mut value, separator)?;
+
+        let variant = match (&key, &value) {
+            (Expression::String(key), Expression::String(_)) => split_variant(key, separator),
+            _ => None,
+        };
+        let Some((grouped, variant)) = variant else {
+            expressions.push((key, value));
+            continue;
+        };
+
+        let index = *groups.entry(grouped.clone()).or_insert_with(|| {
+            expressions.push((grouped.clone().into(), Expression::table(Vec::new())));
+            expressions.len() - 1
+        });
+        if let Expression::Table(group) = &mut expressions[index].1 {
+            group.expressions.push((variant.into(), value));
+        }
+    }
+
+    for (key, value) in &expressions {
+        let Expression::String(key) = key else {
+            continue;
+        };
+        if let Expression::String(path) = value {
+            if groups.contains_key(key) {
+                bail!("{path} would generate the key {key}, which is already used by its variants");
+            }
+        }
+    }
+
+    table.expressions = expressions;
+
+    Ok(())
+}
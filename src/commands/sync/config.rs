@@ -1,16 +1,24 @@
+use super::state::add_trailing_slash;
+use crate::{
+    asset::{AssetType, AudioTranscode, ProcessOptions, ResizeFilter},
+    util::optimize_png::PngOptimization,
+};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::BTreeMap,
     fmt::{self, Display, Formatter},
+    path::PathBuf,
 };
 use tokio::fs::{read_to_string, write};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum CodegenStyle {
     Flat,
     Nested,
+    /// Flat tables grouped by asset type, like `images` and `audio`.
+    ByType,
 }
 
 impl Display for CodegenStyle {
@@ -18,10 +26,64 @@ impl Display for CodegenStyle {
         match self {
             CodegenStyle::Flat => write!(f, "Flat"),
             CodegenStyle::Nested => write!(f, "Nested"),
+            CodegenStyle::ByType => write!(f, "By type"),
         }
     }
 }
 
+/// How keys are ordered in generated code.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum CodegenSort {
+    /// Order keys by their bytes.
+    Bytewise,
+    /// Order runs of digits in keys by their numeric value, so `frame2` comes
+    /// before `frame10`.
+    Natural,
+}
+
+/// How keys derived from file names are cased in generated code.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum CodegenKeyStyle {
+    /// Keep file names as they are.
+    #[serde(rename = "preserve")]
+    Preserve,
+    #[serde(rename = "camelCase")]
+    CamelCase,
+    #[serde(rename = "PascalCase")]
+    PascalCase,
+    #[serde(rename = "snake_case")]
+    SnakeCase,
+}
+
+/// What kind of Typescript file is generated.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TypescriptMode {
+    /// A `.d.ts` file declaring the types of the Luau module.
+    Declaration,
+    /// A `.ts` module with its own values, for projects that don't use the
+    /// Luau module.
+    Source,
+}
+
+/// Text written as a comment at the top of generated files, either inline or
+/// read from a file.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum CodegenHeader {
+    Text(String),
+    File { file: String },
+}
+
+/// What generated code is indented with.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum CodegenIndentType {
+    Tabs,
+    Spaces,
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum CreatorType {
@@ -48,14 +110,65 @@ pub struct Creator {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ExistingAsset {
     pub id: u64,
+    /// Other paths the asset is also generated at.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// A folder in the asset directory the asset and its aliases are placed
+    /// in.
+    pub folder: Option<String>,
+    /// The part of the image to use, for assets that are sprite sheets.
+    pub rect: Option<SpriteRect>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A region of an image, in pixels.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub struct SpriteRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
 pub struct CodegenConfig {
     pub output_name: Option<String>,
+    pub luau: Option<bool>,
     pub typescript: Option<bool>,
+    pub typescript_mode: Option<TypescriptMode>,
     pub style: Option<CodegenStyle>,
     pub strip_extension: Option<bool>,
+    pub luau_types: Option<bool>,
+    pub emit_meta: Option<bool>,
+    pub json: Option<bool>,
+    pub sort: Option<CodegenSort>,
+    pub split_by_folder: Option<bool>,
+    pub content: Option<bool>,
+    pub key_style: Option<CodegenKeyStyle>,
+    pub numeric_ids: Option<bool>,
+    pub include_dimensions: Option<bool>,
+    pub indent_type: Option<CodegenIndentType>,
+    pub indent_width: Option<usize>,
+    pub trailing_commas: Option<bool>,
+    pub header: Option<CodegenHeader>,
+    pub lookup: Option<bool>,
+    pub source_comments: Option<bool>,
+    pub variant_separator: Option<String>,
+    pub frame_sequences: Option<bool>,
+    pub frame_rate: Option<u32>,
+    pub rojo_fragment: Option<String>,
+    pub rojo_path: Option<String>,
+    pub init_shim: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_outputs: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bundles: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preload_order: Vec<String>,
+    pub preload_list: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preload_types: Vec<AssetType>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub slice_centers: BTreeMap<String, SpriteRect>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -63,15 +176,147 @@ pub struct SyncConfig {
     pub asset_dir: String,
     #[serde(default)]
     pub exclude_assets: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bleed_exclude: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub premultiply_alpha: Vec<String>,
     pub write_dir: String,
+    pub ts_write_dir: Option<String>,
     pub creator: Creator,
     pub codegen: CodegenConfig,
-    pub existing: Option<HashMap<String, ExistingAsset>>,
+    pub existing: Option<BTreeMap<String, ExistingAsset>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub type_overrides: BTreeMap<String, AssetType>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub upload_prices: BTreeMap<AssetType, u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_hashes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locked: Vec<String>,
+    pub optimize_png: Option<PngOptimization>,
+    pub svg_scale: Option<f32>,
+    pub convert_to_png: Option<bool>,
+    pub convert_color_profiles: Option<bool>,
+    pub max_image_size: Option<u32>,
+    pub resize_filter: Option<ResizeFilter>,
+    pub transcode_audio: Option<AudioTranscode>,
+    pub trim_silence: Option<f32>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resolution_variants: BTreeMap<String, Vec<u32>>,
+    pub report_dir: Option<String>,
+    pub require_clean_git: Option<bool>,
+    pub commit_message: Option<String>,
+}
+
+impl SyncConfig {
+    /// How assets are changed before they are uploaded.
+    pub fn process_options(&self) -> ProcessOptions {
+        let defaults = ProcessOptions::default();
+
+        ProcessOptions {
+            svg_scale: self.svg_scale.unwrap_or(defaults.svg_scale),
+            alpha_bleed: defaults.alpha_bleed,
+            premultiply_alpha: defaults.premultiply_alpha,
+            convert_to_png: self.convert_to_png.unwrap_or(defaults.convert_to_png),
+            convert_color_profiles: self
+                .convert_color_profiles
+                .unwrap_or(defaults.convert_color_profiles),
+            image_scale: defaults.image_scale,
+            max_image_size: self.max_image_size,
+            resize_filter: self.resize_filter.unwrap_or(defaults.resize_filter),
+            transcode_audio: self.transcode_audio,
+            trim_silence: self.trim_silence,
+        }
+    }
+
+    /// The separator variants are named with. Resolution variants are always
+    /// grouped, so they need one even if it wasn't set.
+    pub fn variant_separator(&self) -> Option<String> {
+        self.codegen
+            .variant_separator
+            .clone()
+            .or_else(|| (!self.resolution_variants.is_empty()).then(|| "@".to_string()))
+    }
+}
+
+impl CodegenConfig {
+    /// Extra directories every generated file is also written to.
+    pub fn extra_output_dirs(&self) -> Vec<PathBuf> {
+        self.extra_outputs
+            .iter()
+            .map(|dir| PathBuf::from(add_trailing_slash(dir)))
+            .collect()
+    }
+
+    /// The paths code generation may write to, relative to the write
+    /// directory, whether or not they are enabled.
+    pub fn output_paths(&self) -> Vec<PathBuf> {
+        let output_name = self.output_name.as_deref().unwrap_or("assets");
+
+        let mut paths = vec![
+            PathBuf::from(format!("{output_name}.luau")),
+            PathBuf::from(format!("{output_name}.d.ts")),
+            PathBuf::from(format!("{output_name}.ts")),
+            PathBuf::from(format!("{output_name}.json")),
+            PathBuf::from(format!("{output_name}_bundles.luau")),
+            PathBuf::from(format!("{output_name}_bundles.d.ts")),
+            PathBuf::from(format!("{output_name}_bundles.ts")),
+            PathBuf::from(format!("{output_name}_bundles.json")),
+            PathBuf::from(format!("{output_name}_preload.luau")),
+            PathBuf::from(format!("{output_name}_preload.d.ts")),
+            PathBuf::from(format!("{output_name}_preload.ts")),
+            PathBuf::from(format!("{output_name}_preload.json")),
+            // Split modules are written to a directory.
+            PathBuf::from(output_name),
+        ];
+
+        // Only claimed when enabled, as it may otherwise be the user's own.
+        if self.init_shim.unwrap_or(false) {
+            paths.push(PathBuf::from("init.luau"));
+        }
+
+        paths
+    }
 }
 
 static FILE_NAME: &str = "asphalt.toml";
 
 impl SyncConfig {
+    /// A config for generating code outside of a project, where assets are
+    /// named by a key mapping rather than their paths.
+    pub fn codegen_only(write_dir: String) -> Self {
+        Self {
+            asset_dir: String::new(),
+            exclude_assets: Vec::new(),
+            bleed_exclude: Vec::new(),
+            premultiply_alpha: Vec::new(),
+            write_dir,
+            ts_write_dir: None,
+            creator: Creator {
+                creator_type: CreatorType::User,
+                id: 0,
+            },
+            codegen: CodegenConfig::default(),
+            existing: None,
+            type_overrides: BTreeMap::new(),
+            upload_prices: BTreeMap::new(),
+            denied_hashes: Vec::new(),
+            locked: Vec::new(),
+            optimize_png: None,
+            svg_scale: None,
+            convert_to_png: None,
+            convert_color_profiles: None,
+            max_image_size: None,
+            resize_filter: None,
+            transcode_audio: None,
+            trim_silence: None,
+            resolution_variants: BTreeMap::new(),
+            report_dir: None,
+            require_clean_git: None,
+            commit_message: None,
+        }
+    }
+
     pub async fn read() -> anyhow::Result<Self> {
         let content = read_to_string(FILE_NAME)
             .await
@@ -79,6 +324,27 @@ impl SyncConfig {
         toml::from_str(&content).context("Failed to parse config")
     }
 
+    /// Reads the header for generated files, replacing `{version}` with the
+    /// Asphalt version and `{config_hash}` with a hash of this config.
+    pub async fn codegen_header(&self) -> anyhow::Result<Option<String>> {
+        let header = match &self.codegen.header {
+            Some(CodegenHeader::Text(text)) => text.clone(),
+            Some(CodegenHeader::File { file }) => read_to_string(file)
+                .await
+                .with_context(|| format!("Failed to read codegen header from {file}"))?,
+            None => return Ok(None),
+        };
+
+        let config_hash = blake3::hash(toml::to_string(self)?.as_bytes()).to_string();
+
+        Ok(Some(
+            header
+                .trim_end()
+                .replace("{version}", env!("CARGO_PKG_VERSION"))
+                .replace("{config_hash}", &config_hash),
+        ))
+    }
+
     pub async fn write(&self) -> anyhow::Result<()> {
         let content = toml::to_string(self)?;
         write(FILE_NAME, content)
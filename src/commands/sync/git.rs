@@ -0,0 +1,47 @@
+use anyhow::{bail, Context};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn run(command: &mut Command) -> anyhow::Result<String> {
+    let output = command.output().context("Failed to run git")?;
+
+    if !output.status.success() {
+        bail!(
+            "git failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// Fails if git reports uncommitted changes, including untracked files, under
+/// a path.
+pub fn ensure_clean(path: &Path) -> anyhow::Result<()> {
+    let changes = run(Command::new("git")
+        .args(["status", "--porcelain", "--"])
+        .arg(path))?;
+
+    if !changes.is_empty() {
+        bail!(
+            "Refused to sync because {} has uncommitted changes. Commit them first:\n{changes}",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Commits only the given files, leaving anything else that is staged alone.
+pub fn commit(files: &[PathBuf], message: &str) -> anyhow::Result<()> {
+    run(Command::new("git").args(["add", "--"]).args(files))?;
+    run(Command::new("git")
+        .args(["commit", "--message", message, "--"])
+        .args(files))?;
+
+    Ok(())
+}
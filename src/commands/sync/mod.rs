@@ -1,36 +1,286 @@
-use self::state::SyncState;
+use self::state::{add_trailing_slash, SyncState};
 use crate::{
-    asset::Asset,
-    cli::{SyncArgs, SyncTarget},
-    FileEntry, LockFile,
+    asset::{find_content_issue, unsupported_format_hint, Asset, AssetType},
+    cli::{CodegenLang, SyncArgs, SyncTarget},
+    upload::{error_request_id, is_offline_error},
+    util::path::normalize,
+    FileEntry, LockFile, LockFileDelta,
 };
-use anyhow::Context;
+use anyhow::{bail, Context};
 use backend::{
     cloud::CloudBackend, debug::DebugBackend, studio::StudioBackend, SyncBackend, SyncResult,
 };
-use codegen::{generate_luau, generate_ts};
-use config::SyncConfig;
+use budget::confirm_spend;
+use codegen::{generate_files, generate_node_tree, CodegenMeta, CodegenOptions};
+use config::{ExistingAsset, SyncConfig};
+use globset::Glob;
 use log::{debug, info, warn};
+use report::SkipReason;
+use rojo::RojoFragment;
 use std::{
     collections::BTreeMap,
+    iter,
     path::{Path, PathBuf},
+    time::Instant,
 };
-use tokio::fs::{read, write};
+use tokio::fs::{create_dir_all, read, read_to_string, write};
 use walkdir::{DirEntry, WalkDir};
 
 mod backend;
+mod budget;
 mod codegen;
 pub mod config;
+mod git;
+mod processing;
+mod report;
+mod resolution;
+mod rojo;
 mod state;
+mod trace;
+mod verify;
+
+pub use verify::{check_codegen, verify};
 
 fn fix_path(path: &str) -> String {
     path.replace('\\', "/")
 }
 
+fn walk_assets(state: &mut SyncState) -> anyhow::Result<Vec<DirEntry>> {
+    let mut entries = Vec::new();
+
+    for entry in WalkDir::new(&state.asset_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.path().is_file())
+    {
+        let normalized = normalize(entry.path());
+        let generated = state
+            .generated_paths
+            .iter()
+            .any(|path| normalized.starts_with(path));
+        if generated {
+            continue;
+        }
+
+        if state.exclude_assets_matcher.is_match(entry.path()) {
+            let path = fix_path(&entry.path().to_string_lossy());
+            state.report.skip(&path, SkipReason::Excluded);
+        } else if entry.path().to_str().is_none() {
+            // Paths become keys in the generated code, which must be valid
+            // strings.
+            let path = fix_path(&entry.path().to_string_lossy());
+            state.report.skip(&path, SkipReason::InvalidName);
+            state.warn(format!("Skipping {path}, as its name is not valid UTF-8"))?;
+        } else {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
 fn format_asset_id(asset_id: u64) -> String {
     format!("rbxassetid://{}", asset_id)
 }
 
+/// Adds assets that were uploaded outside of Asphalt, resolving their paths
+/// relative to the asset directory.
+fn add_existing_assets(
+    assets: &mut BTreeMap<String, String>,
+    existing: &BTreeMap<String, ExistingAsset>,
+    asset_dir: &str,
+    options: &mut CodegenOptions,
+) {
+    for (path, asset) in existing {
+        for path in iter::once(path).chain(&asset.aliases) {
+            let mut path = match &asset.folder {
+                Some(folder) => PathBuf::from(folder).join(path),
+                None => PathBuf::from(path),
+            };
+
+            if !path.starts_with(asset_dir) {
+                path = PathBuf::from(asset_dir).join(path);
+            }
+
+            let path = path.to_str().unwrap().to_string();
+
+            if let Some(rect) = asset.rect {
+                options.rects.insert(path.clone(), rect);
+            }
+            assets.insert(path, format_asset_id(asset.id));
+        }
+    }
+}
+
+/// Writes a file, creating its parent directories, unless it already has the
+/// same contents. Returns whether it was written.
+async fn write_if_changed(path: &Path, contents: &str) -> anyhow::Result<bool> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let unchanged = read_to_string(path)
+        .await
+        .is_ok_and(|existing| existing == contents);
+    if unchanged {
+        return Ok(false);
+    }
+
+    write(path, contents)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(true)
+}
+
+/// Where a generated file is written: the Typescript write directory for
+/// Typescript files and the write directory for everything else, plus every
+/// extra output directory.
+fn generated_file_paths(
+    write_dir: &Path,
+    ts_write_dir: &Path,
+    extra_dirs: &[PathBuf],
+    path: &Path,
+) -> Vec<PathBuf> {
+    let mut paths = if path.extension().is_some_and(|extension| extension == "ts") {
+        vec![ts_write_dir.join(path)]
+    } else {
+        vec![write_dir.join(path)]
+    };
+    paths.extend(extra_dirs.iter().map(|dir| dir.join(path)));
+    paths
+}
+
+/// Writes generated files to where they belong, returning the paths of those
+/// whose contents changed.
+async fn write_generated_files(
+    write_dir: &Path,
+    ts_write_dir: &Path,
+    extra_dirs: &[PathBuf],
+    generated: &BTreeMap<PathBuf, String>,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+
+    for (path, contents) in generated {
+        for path in generated_file_paths(write_dir, ts_write_dir, extra_dirs, path) {
+            if write_if_changed(&path, contents)
+                .await
+                .context("Failed to write generated file")?
+            {
+                changed.push(path);
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Writes the tree of generated keys as JSON, which YAML parsers also read.
+async fn write_node_tree(
+    path: &Path,
+    assets: &BTreeMap<String, String>,
+    asset_dir: &str,
+    options: &CodegenOptions,
+) -> anyhow::Result<()> {
+    let tree = generate_node_tree(assets, asset_dir, options)?;
+    let json = serde_json::to_string_pretty(&tree)?;
+
+    write(path, json)
+        .await
+        .with_context(|| format!("Failed to write node tree to {}", path.display()))
+}
+
+/// Options for a sync, independent of the command line.
+pub struct SyncOptions {
+    pub config: SyncConfig,
+    pub existing_lockfile: LockFile,
+
+    /// Your Open Cloud API key. Falls back to the ASPHALT_API_KEY environment
+    /// variable.
+    pub api_key: Option<String>,
+    /// Your cookie, which is only required for uploading animations. Falls
+    /// back to the ASPHALT_COOKIE environment variable, then Roblox Studio.
+    pub cookie: Option<String>,
+    pub target: SyncTarget,
+    /// Skip asset syncing and only log what assets will be synced.
+    pub dry_run: bool,
+    /// The most Robux this sync may spend on paid uploads. When unset, paid
+    /// uploads must be confirmed interactively.
+    pub budget: Option<u64>,
+    /// Refuse to sync to the cloud if assets have uncommitted changes, even
+    /// if the config does not require it.
+    pub require_clean_git: bool,
+    /// Fail on anything that would otherwise only be a warning.
+    pub strict: bool,
+    /// Fail on anything that would contact Roblox.
+    pub offline: bool,
+    /// Where to write a trace of how long each part of the sync took.
+    pub trace: Option<PathBuf>,
+    /// Where to write the tree of generated keys as JSON.
+    pub emit_node_tree: Option<PathBuf>,
+}
+
+impl SyncOptions {
+    pub fn new(config: SyncConfig, existing_lockfile: LockFile) -> Self {
+        Self {
+            config,
+            existing_lockfile,
+            api_key: None,
+            cookie: None,
+            target: SyncTarget::Cloud,
+            dry_run: false,
+            budget: None,
+            require_clean_git: false,
+            strict: false,
+            offline: false,
+            trace: None,
+            emit_node_tree: None,
+        }
+    }
+
+    pub fn from_args(
+        args: SyncArgs,
+        config: SyncConfig,
+        existing_lockfile: LockFile,
+        offline: bool,
+    ) -> Self {
+        Self {
+            config,
+            existing_lockfile,
+            api_key: args.api_key,
+            cookie: args.cookie,
+            target: args.target.unwrap_or(SyncTarget::Cloud),
+            dry_run: args.dry_run,
+            budget: args.budget,
+            require_clean_git: args.require_clean_git,
+            strict: args.strict,
+            offline,
+            trace: args.trace,
+            emit_node_tree: args.emit_node_tree,
+        }
+    }
+}
+
+/// The result of a sync.
+#[derive(Debug, Default)]
+pub struct SyncOutput {
+    /// The number of assets that were synced.
+    pub synced: usize,
+    /// Every asset path referenced by the generated code, mapped to its ID.
+    pub assets: BTreeMap<String, String>,
+    /// Assets uploaded during this sync, mapped to their new asset IDs.
+    pub uploaded: BTreeMap<String, u64>,
+    /// The generated files, keyed by their path relative to the write
+    /// directory. Empty if code generation did not run.
+    pub generated: BTreeMap<PathBuf, String>,
+    /// How the lockfile changed. Only the cloud target writes a lockfile.
+    pub lockfile_delta: LockFileDelta,
+    /// Every file whose contents were changed by this sync.
+    pub changed_files: Vec<PathBuf>,
+}
+
 enum TargetBackend {
     Cloud(CloudBackend),
     Studio(StudioBackend),
@@ -46,7 +296,7 @@ async fn process_file(
     entry: &DirEntry,
     state: &mut SyncState,
     backend: &TargetBackend,
-) -> anyhow::Result<Option<ProcessResult>> {
+) -> anyhow::Result<Vec<(String, Option<ProcessResult>)>> {
     let path = entry.path();
     let path_str = path.to_str().unwrap();
     let fixed_path = fix_path(path_str);
@@ -67,13 +317,131 @@ async fn process_file(
     let ext = match path.extension().and_then(|s| s.to_str()) {
         Some(extension) => extension,
         None => {
-            warn!("Failed to get extension of {fixed_path}");
-            return Ok(None);
+            state.report.skip(&fixed_path, SkipReason::NoExtension);
+            state.warn(format!("Failed to get extension of {fixed_path}"))?;
+            return Ok(Vec::new());
         }
     };
 
-    let asset = Asset::new(file_name, data, ext, state.fontdb.clone()).await?;
+    let type_override = state.type_override(path_str)?;
+
+    if type_override.is_none() && AssetType::from_extension(ext).is_none() {
+        state.report.skip(&fixed_path, SkipReason::UnknownExtension);
+        let message = match unsupported_format_hint(ext) {
+            Some(hint) => format!("{fixed_path} has an unknown extension .{ext}. {hint}"),
+            None => format!("{fixed_path} has an unknown extension .{ext}"),
+        };
+        state.warn(message)?;
+        return Ok(Vec::new());
+    }
+
+    if let Some(issue) = find_content_issue(&data, ext) {
+        let reason = if data.is_empty() {
+            SkipReason::Empty
+        } else {
+            SkipReason::Corrupt
+        };
+        state.report.skip(&fixed_path, reason);
+        state.warn(format!(
+            "Skipping {fixed_path} ({} bytes), as {issue}",
+            data.len()
+        ))?;
+        return Ok(Vec::new());
+    }
+
+    let variants = state.processing.assets(path_str, &fixed_path, ext)?;
+
+    let mut results = Vec::new();
+    for (asset_path, process_options) in variants {
+        let asset = match state.processed_assets.remove(&asset_path) {
+            Some(asset) => asset,
+            None => {
+                let mut asset = Asset::new(
+                    file_name.clone(),
+                    data.clone(),
+                    ext,
+                    state.fontdb.clone(),
+                    type_override,
+                    &process_options,
+                )
+                .await?;
+                if let Some(optimization) = state.optimize_png {
+                    asset
+                        .optimize(optimization)
+                        .with_context(|| format!("Failed to optimize {asset_path}"))?;
+                }
+                asset
+            }
+        };
+
+        let result = sync_asset(state, backend, path_str, &asset_path, asset).await?;
+        results.push((asset_path, result));
+    }
+
+    Ok(results)
+}
+
+/// Uploads a processed asset, unless it is denied, locked, or this is a dry
+/// run. `path` is the file it was made from, which `fixed_path` is only
+/// different to for resolution variants.
+async fn sync_asset(
+    state: &mut SyncState,
+    backend: &TargetBackend,
+    path: &str,
+    fixed_path: &str,
+    asset: Asset,
+) -> anyhow::Result<Option<ProcessResult>> {
+    let fixed_path = fixed_path.to_string();
+    for warning in asset.warnings() {
+        state.warn(warning)?;
+    }
+
     let hash = asset.hash();
+    state.report.add(&fixed_path, asset.processing());
+    if let Some(dimensions) = asset.processing().dimensions {
+        state
+            .codegen
+            .dimensions
+            .insert(fixed_path.clone(), dimensions);
+    }
+    state
+        .codegen
+        .hashes
+        .insert(fixed_path.clone(), hash.clone());
+    state
+        .codegen
+        .asset_types
+        .insert(fixed_path.clone(), asset.kind().asset_type());
+    state
+        .codegen
+        .sizes
+        .insert(fixed_path.clone(), asset.processing().size);
+    if let Some(flipbook) = asset.flipbook() {
+        state
+            .codegen
+            .flipbooks
+            .insert(fixed_path.clone(), flipbook.clone());
+    }
+
+    if state.denied_hashes.contains(&hash) {
+        warn!("{fixed_path} has a denied hash and will not be uploaded");
+        state.denied_assets.push(fixed_path);
+        return Ok(None);
+    }
+
+    if state.locked_matcher.is_match(path) {
+        let changed = state
+            .existing_lockfile
+            .entries
+            .get(&fixed_path)
+            .is_some_and(|entry| entry.hash != hash);
+
+        if changed {
+            warn!("{fixed_path} is locked and has changed since it was last uploaded");
+            state.locked_assets.push(fixed_path);
+            return Ok(None);
+        }
+    }
 
     if state.dry_run {
         info!("Sync {fixed_path}");
@@ -90,7 +458,15 @@ async fn process_file(
     match sync_result {
         SyncResult::Cloud(asset_id) => Ok(Some(ProcessResult {
             asset_id: format_asset_id(asset_id),
-            file_entry: Some(FileEntry { hash, asset_id }),
+            file_entry: Some(FileEntry {
+                hash,
+                asset_id,
+                note: state
+                    .existing_lockfile
+                    .entries
+                    .get(&fixed_path)
+                    .and_then(|entry| entry.note.clone()),
+            }),
         })),
         SyncResult::Studio(asset_id) => Ok(Some(ProcessResult {
             asset_id,
@@ -100,10 +476,61 @@ async fn process_file(
     }
 }
 
-pub async fn sync(args: SyncArgs, existing_lockfile: LockFile) -> anyhow::Result<()> {
-    let config = SyncConfig::read().await.context("Failed to read config")?;
+/// Fails before anything is uploaded if a file is overridden to a type Open
+/// Cloud can't upload it as.
+fn check_cloud_overrides(state: &SyncState, entries: &[DirEntry]) -> anyhow::Result<()> {
+    let mut rejected = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        let (Some(path_str), Some(ext)) =
+            (path.to_str(), path.extension().and_then(|e| e.to_str()))
+        else {
+            continue;
+        };
 
-    let mut state = SyncState::new(args, config, existing_lockfile)
+        if matches!(ext, "rbxm" | "rbxmx")
+            && state.type_override(path_str)? == Some(AssetType::Model)
+        {
+            rejected.push(fix_path(path_str));
+        }
+    }
+
+    if !rejected.is_empty() {
+        bail!(
+            "Open Cloud only accepts models as .fbx files, so .rbxm and .rbxmx files overridden to model can only be synced with --target studio or debug:\n{}",
+            rejected.join("\n")
+        );
+    }
+
+    Ok(())
+}
+
+/// Writes the lockfile before a sync is refused, so the assets that were
+/// uploaded before it aren't uploaded again. Refused assets keep the entries
+/// they had.
+async fn write_refused_lockfile(state: &mut SyncState) -> anyhow::Result<()> {
+    if state.dry_run || !matches!(state.target, SyncTarget::Cloud) {
+        return Ok(());
+    }
+
+    for path in state.denied_assets.iter().chain(&state.locked_assets) {
+        if let Some(entry) = state.existing_lockfile.entries.get(path) {
+            state
+                .new_lockfile
+                .entries
+                .insert(path.clone(), entry.clone());
+        }
+    }
+
+    state
+        .new_lockfile
+        .write(Path::new(crate::lockfile::FILE_NAME))
+        .await
+        .context("Failed to write lockfile")
+}
+
+pub async fn sync(options: SyncOptions) -> anyhow::Result<SyncOutput> {
+    let mut state = SyncState::new(options)
         .await
         .context("Failed to create state")?;
 
@@ -118,105 +545,310 @@ pub async fn sync(args: SyncArgs, existing_lockfile: LockFile) -> anyhow::Result
         SyncTarget::Debug => TargetBackend::Debug(DebugBackend::new().await?),
     };
 
-    for entry in WalkDir::new(&state.asset_dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
+    let started = Instant::now();
+    let entries = walk_assets(&mut state)?;
+    state.trace("walk", started, None);
+
+    if let (SyncTarget::Cloud, false, true) =
+        (&state.target, state.dry_run, state.require_clean_git)
     {
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
+        git::ensure_clean(&state.asset_dir)?;
+    }
+
+    if let SyncTarget::Cloud = state.target {
+        check_cloud_overrides(&state, &entries)?;
 
+        let started = Instant::now();
+        confirm_spend(&mut state, &entries)
+            .await
+            .context("Failed to confirm paid uploads")?;
+        state.trace("confirm spend", started, None);
+    }
+
+    for entry in entries {
+        let path = entry.path();
         let path_str = path.to_str().unwrap();
 
         let fixed_path = fix_path(path_str);
 
-        if state.exclude_assets_matcher.is_match(path_str) {
-            continue;
-        }
+        let started = Instant::now();
+        let result = process_file(&entry, &mut state, &backend).await;
+        state.trace("process", started, Some(&fixed_path));
 
-        let result = match process_file(&entry, &mut state, &backend).await {
-            Ok(Some(result)) => {
-                synced += 1;
-                result
+        let results = match result {
+            Ok(results) if results.is_empty() && state.report.was_just_skipped(&fixed_path) => {
+                continue
             }
-            Ok(None) => {
-                synced += 1;
-                continue;
+            Ok(results) => results,
+            Err(e) if state.strict || is_offline_error(&e) => {
+                return Err(e.context(format!("Failed to process file {fixed_path}")));
             }
             Err(e) => {
                 warn!("Failed to process file {fixed_path}: {e:?}");
+                state.report.fail(&fixed_path, error_request_id(&e));
                 continue;
             }
         };
 
-        assets.insert(fixed_path.clone(), result.asset_id);
-        if let Some(file_entry) = result.file_entry {
-            state.new_lockfile.entries.insert(fixed_path, file_entry);
+        for (asset_path, result) in results {
+            synced += 1;
+            let Some(result) = result else {
+                continue;
+            };
+
+            assets.insert(asset_path.clone(), result.asset_id);
+            if let Some(file_entry) = result.file_entry {
+                state.new_lockfile.entries.insert(asset_path, file_entry);
+            }
         }
     }
 
+    state.report.log_skipped();
+    state.report.log_color_profiles();
+
+    if !state.denied_assets.is_empty() || !state.locked_assets.is_empty() {
+        write_refused_lockfile(&mut state).await?;
+    }
+
+    if !state.denied_assets.is_empty() {
+        bail!(
+            "Refused to sync assets with denied hashes:\n{}",
+            state.denied_assets.join("\n")
+        );
+    }
+
+    if !state.locked_assets.is_empty() {
+        bail!(
+            "Refused to sync locked assets that have changed. Restore them, or remove them from `locked` to upload a new version:\n{}",
+            state.locked_assets.join("\n")
+        );
+    }
+
+    if let (false, Some(report_dir)) = (state.dry_run, &state.report_dir) {
+        state
+            .report
+            .write(report_dir)
+            .await
+            .context("Failed to write processing report")?;
+    }
+
+    let mut output = SyncOutput {
+        synced,
+        ..Default::default()
+    };
+
     if state.dry_run || matches!(state.target, SyncTarget::Debug) {
+        state.write_trace().await?;
         info!(
             "Synced {} asset{}!",
             synced,
             if synced == 1 { "" } else { "s" }
         );
-        return Ok(());
+        output.assets = assets;
+        return Ok(output);
     }
 
     if let SyncTarget::Cloud = state.target {
+        let lockfile_path = Path::new(crate::lockfile::FILE_NAME);
+        let lockfile_existed = lockfile_path.exists();
+
         state
             .new_lockfile
-            .write(Path::new(crate::lockfile::FILE_NAME))
+            .write(lockfile_path)
             .await
             .context("Failed to write lockfile")?;
+
+        output.lockfile_delta = state.new_lockfile.delta(&state.existing_lockfile);
+        if !lockfile_existed || !output.lockfile_delta.is_empty() {
+            output.changed_files.push(lockfile_path.to_path_buf());
+        }
+
+        output.uploaded = output
+            .lockfile_delta
+            .added
+            .iter()
+            .chain(output.lockfile_delta.changed.iter())
+            .map(|(path, entry)| (path.clone(), entry.asset_id))
+            .collect();
     }
 
     let asset_dir = state.asset_dir.to_str().unwrap();
 
-    assets.extend(state.existing.into_iter().map(|(path, asset)| {
-        let mut path = PathBuf::from(path);
+    add_existing_assets(&mut assets, &state.existing, asset_dir, &mut state.codegen);
+    state.codegen.match_slice_centers(&assets)?;
+    state.codegen.match_premultiplied(&assets)?;
+
+    if state.emit_meta {
+        state.codegen.meta = Some(CodegenMeta::now(assets.len()));
+    }
 
-        if !path.starts_with(asset_dir) {
-            path = PathBuf::from(asset_dir).join(path);
+    let started = Instant::now();
+    let generated = generate_files(&assets, asset_dir, &state.codegen)?;
+    if let Some(path) = &state.emit_node_tree {
+        write_node_tree(path, &assets, asset_dir, &state.codegen).await?;
+    }
+    state.trace("codegen", started, None);
+
+    let started = Instant::now();
+    output.changed_files.extend(
+        write_generated_files(
+            &state.write_dir,
+            &state.ts_write_dir,
+            &state.extra_output_dirs,
+            &generated,
+        )
+        .await?,
+    );
+    if let Some(rojo) = &state.rojo_fragment {
+        output
+            .changed_files
+            .extend(rojo.write(&state.write_dir, &state.codegen).await?);
+    }
+    output.generated = generated;
+    state.trace("write", started, None);
+
+    if let (SyncTarget::Cloud, Some(template)) = (&state.target, &state.commit_message) {
+        if !output.changed_files.is_empty() {
+            let message = template.replace("{count}", &output.uploaded.len().to_string());
+            git::commit(&output.changed_files, &message)
+                .context("Failed to commit changed files")?;
         }
+    }
+
+    state.write_trace().await?;
+
+    info!(
+        "Synced {} asset{}!",
+        synced,
+        if synced == 1 { "" } else { "s" }
+    );
 
-        let path = path.to_str().unwrap().to_string();
+    output.assets = assets;
 
-        (path, format_asset_id(asset.id))
-    }));
+    Ok(output)
+}
 
-    let luau_filename = format!("{}.{}", state.output_name, "luau");
-    let luau_output = generate_luau(&assets, asset_dir, &state.style, state.strip_extension);
+/// Everything needed to generate code from the lockfile alone.
+struct LockfileCodegen {
+    asset_dir: String,
+    write_dir: PathBuf,
+    ts_write_dir: PathBuf,
+    extra_output_dirs: Vec<PathBuf>,
+    options: CodegenOptions,
+    /// Every asset in the lockfile and `existing`, mapped to its ID.
+    assets: BTreeMap<String, String>,
+}
 
-    write(
-        Path::new(&state.write_dir).join(luau_filename),
-        luau_output?,
-    )
-    .await
-    .context("Failed to write output Luau file")?;
+impl LockfileCodegen {
+    async fn new(config: &SyncConfig, lockfile: &LockFile) -> anyhow::Result<Self> {
+        let asset_dir = add_trailing_slash(&config.asset_dir);
+        let write_dir = PathBuf::from(add_trailing_slash(&config.write_dir));
+        let ts_write_dir = config
+            .ts_write_dir
+            .as_deref()
+            .map(|dir| PathBuf::from(add_trailing_slash(dir)))
+            .unwrap_or_else(|| write_dir.clone());
+        let extra_output_dirs = config.codegen.extra_output_dirs();
+
+        let mut options = CodegenOptions::new(&config.codegen);
+        options.header = config.codegen_header().await?;
+        options.variant_separator = config.variant_separator();
+        options.premultiplied_globs = config.premultiply_alpha.clone();
+
+        let mut type_overrides = Vec::new();
+        for (glob, asset_type) in &config.type_overrides {
+            type_overrides.push((Glob::new(glob)?.compile_matcher(), *asset_type));
+        }
 
-    if state.typescript {
-        let ts_filename = format!("{}.d.ts", state.output_name);
-        let ts_output = generate_ts(
-            &assets,
+        let mut assets = BTreeMap::<String, String>::new();
+        for (path, entry) in &lockfile.entries {
+            if let Some((_, asset_type)) = type_overrides
+                .iter()
+                .find(|(matcher, _)| matcher.is_match(path))
+            {
+                options.asset_types.insert(path.clone(), *asset_type);
+            }
+
+            assets.insert(path.clone(), format_asset_id(entry.asset_id));
+            options.hashes.insert(path.clone(), entry.hash.clone());
+        }
+        if let Some(existing) = &config.existing {
+            add_existing_assets(&mut assets, existing, &asset_dir, &mut options);
+        }
+        options.match_slice_centers(&assets)?;
+        options.match_premultiplied(&assets)?;
+
+        Ok(Self {
             asset_dir,
-            state.output_name.as_str(),
-            &state.style,
-            state.strip_extension,
-        );
+            write_dir,
+            ts_write_dir,
+            extra_output_dirs,
+            options,
+            assets,
+        })
+    }
+}
 
-        write(Path::new(&state.write_dir).join(ts_filename), ts_output?)
-            .await
-            .context("Failed to write output TypeScript file")?;
+/// Reads a TOML file mapping asset paths to the keys they are generated
+/// with.
+pub async fn read_key_mapping(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let content = read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Regenerates code from the lockfile without syncing any assets, returning
+/// the paths of the files that changed. Only assets in the lockfile and
+/// `existing` are included. With `keys`, assets are named by it rather than
+/// their paths.
+pub async fn generate(
+    config: SyncConfig,
+    lockfile: LockFile,
+    lang: Option<CodegenLang>,
+    keys: Option<BTreeMap<String, String>>,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let LockfileCodegen {
+        asset_dir,
+        write_dir,
+        ts_write_dir,
+        extra_output_dirs,
+        mut options,
+        assets,
+    } = LockfileCodegen::new(&config, &lockfile).await?;
+
+    options.keys = keys;
+    match lang {
+        Some(CodegenLang::Luau) => {
+            options.luau = true;
+            options.typescript = false;
+            options.json = false;
+        }
+        Some(CodegenLang::Ts) => {
+            options.luau = false;
+            options.typescript = true;
+            options.json = false;
+        }
+        None => {}
+    }
+
+    if config.codegen.emit_meta.unwrap_or(false) {
+        options.meta = Some(CodegenMeta::now(assets.len()));
+    }
+
+    let generated = generate_files(&assets, &asset_dir, &options)?;
+    let mut changed =
+        write_generated_files(&write_dir, &ts_write_dir, &extra_output_dirs, &generated).await?;
+    if let Some(rojo) = RojoFragment::from_config(&config.codegen)? {
+        changed.extend(rojo.write(&write_dir, &options).await?);
     }
 
     info!(
-        "Synced {} asset{}!",
-        synced,
-        if synced == 1 { "" } else { "s" }
+        "Generated {} file{}",
+        generated.len(),
+        if generated.len() == 1 { "" } else { "s" }
     );
 
-    Ok(())
+    Ok(changed)
 }
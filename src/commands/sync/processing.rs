@@ -0,0 +1,57 @@
+use super::{config::SyncConfig, resolution::ResolutionVariants};
+use crate::asset::ProcessOptions;
+use anyhow::Context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Decides how each file is processed, which can depend on its path.
+pub struct FileProcessing {
+    options: ProcessOptions,
+    bleed_exclude: GlobSet,
+    premultiply_alpha: GlobSet,
+    resolution_variants: ResolutionVariants,
+}
+
+impl FileProcessing {
+    pub fn new(config: &SyncConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            options: config.process_options(),
+            bleed_exclude: glob_set(&config.bleed_exclude, "bleed_exclude")?,
+            premultiply_alpha: glob_set(&config.premultiply_alpha, "premultiply_alpha")?,
+            resolution_variants: ResolutionVariants::new(config)?,
+        })
+    }
+
+    /// The path each asset made from a file is synced under, with how it is
+    /// processed.
+    pub fn assets(
+        &self,
+        path: &str,
+        fixed_path: &str,
+        ext: &str,
+    ) -> anyhow::Result<Vec<(String, ProcessOptions)>> {
+        let options = ProcessOptions {
+            alpha_bleed: !self.bleed_exclude.is_match(path),
+            ..self.options
+        };
+
+        let mut assets = self
+            .resolution_variants
+            .expand(path, fixed_path, ext, &options)?;
+        // Matched against the path an asset is synced under, which is what
+        // generated code marks it by.
+        for (asset_path, options) in &mut assets {
+            options.premultiply_alpha = self.premultiply_alpha.is_match(asset_path.as_str());
+        }
+
+        Ok(assets)
+    }
+}
+
+fn glob_set(globs: &[String], option: &str) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).with_context(|| format!("Invalid {option} glob {glob}"))?);
+    }
+
+    Ok(builder.build()?)
+}
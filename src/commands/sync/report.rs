@@ -0,0 +1,261 @@
+//! A per-run report of what processing did to each asset, so artists can see
+//! why an uploaded asset differs from the file they exported, and which files
+//! were skipped.
+
+use crate::asset::ProcessingDelta;
+use anyhow::Context;
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter, Write},
+    path::Path,
+};
+use tokio::fs::{create_dir_all, write};
+
+#[derive(Debug, Serialize)]
+struct ReportEntry {
+    path: String,
+    #[serde(flatten)]
+    delta: ProcessingDelta,
+}
+
+/// Why a file in the asset directory is missing from the generated code.
+#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    Excluded,
+    InvalidName,
+    NoExtension,
+    UnknownExtension,
+    Empty,
+    Corrupt,
+    Failed,
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Excluded => write!(f, "excluded"),
+            SkipReason::InvalidName => write!(f, "name is not valid UTF-8"),
+            SkipReason::NoExtension => write!(f, "no extension"),
+            SkipReason::UnknownExtension => write!(f, "unknown extension"),
+            SkipReason::Empty => write!(f, "empty"),
+            SkipReason::Corrupt => write!(f, "corrupt or mislabeled"),
+            SkipReason::Failed => write!(f, "failed to process"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SkippedEntry {
+    path: String,
+    reason: SkipReason,
+    /// The ID of the Roblox request that failed, for support tickets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProcessingReport {
+    assets: Vec<ReportEntry>,
+    skipped: Vec<SkippedEntry>,
+}
+
+fn format_dimensions(dimensions: Option<(u32, u32)>) -> String {
+    dimensions
+        .map(|(width, height)| format!("{width}x{height}"))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl ProcessingReport {
+    /// Records an asset, if processing changed it.
+    pub fn add(&mut self, path: &str, delta: &ProcessingDelta) {
+        if delta.is_changed() {
+            self.assets.push(ReportEntry {
+                path: path.to_string(),
+                delta: delta.clone(),
+            });
+        }
+    }
+
+    pub fn skip(&mut self, path: &str, reason: SkipReason) {
+        self.skipped.push(SkippedEntry {
+            path: path.to_string(),
+            reason,
+            request_id: None,
+        });
+    }
+
+    /// Records a file that failed to process or upload, with the ID of the
+    /// Roblox request that failed, if there was one.
+    pub fn fail(&mut self, path: &str, request_id: Option<&str>) {
+        self.skipped.push(SkippedEntry {
+            path: path.to_string(),
+            reason: SkipReason::Failed,
+            request_id: request_id.map(str::to_string),
+        });
+    }
+
+    /// Whether the most recently skipped file is this one.
+    pub fn was_just_skipped(&self, path: &str) -> bool {
+        self.skipped.last().is_some_and(|entry| entry.path == path)
+    }
+
+    fn skipped_by_reason(&self) -> BTreeMap<SkipReason, Vec<&SkippedEntry>> {
+        let mut by_reason: BTreeMap<SkipReason, Vec<&SkippedEntry>> = BTreeMap::new();
+        for entry in &self.skipped {
+            by_reason.entry(entry.reason).or_default().push(entry);
+        }
+        by_reason
+    }
+
+    /// Logs how many files were skipped for each reason.
+    pub fn log_skipped(&self) {
+        for (reason, entries) in self.skipped_by_reason() {
+            let paths: Vec<_> = entries.iter().map(|entry| entry.path.as_str()).collect();
+            info!(
+                "Skipped {} file{} ({reason}):\n  {}",
+                paths.len(),
+                if paths.len() == 1 { "" } else { "s" },
+                paths.join("\n  ")
+            );
+        }
+    }
+
+    /// Warns about every image that was converted from another colour
+    /// profile, as its colours may look different to what was expected.
+    pub fn log_color_profiles(&self) {
+        let converted: Vec<_> = self
+            .assets
+            .iter()
+            .filter_map(|entry| {
+                let profile = entry.delta.color_profile.as_ref()?;
+                Some(format!("{} ({profile})", entry.path))
+            })
+            .collect();
+        if converted.is_empty() {
+            return;
+        }
+
+        warn!(
+            "Converted {} image{} to sRGB from {} colour profile:
+  {}",
+            converted.len(),
+            if converted.len() == 1 { "" } else { "s" },
+            if converted.len() == 1 { "its" } else { "their" },
+            converted.join("\n  ")
+        );
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut markdown = String::from("# Processing report\n\n");
+
+        if self.assets.is_empty() {
+            markdown.push_str("No assets were changed by processing.\n");
+            self.write_skipped_markdown(&mut markdown);
+            return markdown;
+        }
+
+        markdown.push_str("| Asset | Format | Size | Dimensions |\n");
+        markdown.push_str("| --- | --- | --- | --- |\n");
+
+        for entry in &self.assets {
+            let delta = &entry.delta;
+
+            let mut format = if delta.source_extension == delta.extension {
+                delta.extension.clone()
+            } else {
+                format!("{} → {}", delta.source_extension, delta.extension)
+            };
+            if let Some(profile) = &delta.color_profile {
+                let _ = write!(format, " ({profile} → sRGB)");
+            }
+
+            let dimensions = if delta.source_dimensions.is_none()
+                || delta.source_dimensions == delta.dimensions
+            {
+                format_dimensions(delta.dimensions)
+            } else {
+                format!(
+                    "{} → {}",
+                    format_dimensions(delta.source_dimensions),
+                    format_dimensions(delta.dimensions)
+                )
+            };
+
+            let _ = writeln!(
+                markdown,
+                "| {} | {format} | {} B → {} B | {dimensions} |",
+                entry.path, delta.source_size, delta.size
+            );
+        }
+
+        self.write_skipped_markdown(&mut markdown);
+        markdown
+    }
+
+    fn write_skipped_markdown(&self, markdown: &mut String) {
+        if self.skipped.is_empty() {
+            return;
+        }
+
+        markdown.push_str("\n## Skipped\n");
+        for (reason, entries) in self.skipped_by_reason() {
+            let _ = write!(markdown, "\n### {reason}\n\n");
+            for entry in entries {
+                match &entry.request_id {
+                    Some(id) => {
+                        let _ = writeln!(markdown, "- {} (request ID: {id})", entry.path);
+                    }
+                    None => {
+                        let _ = writeln!(markdown, "- {}", entry.path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes the report to `report.md` and `report.json` in a directory.
+    pub async fn write(&self, dir: &Path) -> anyhow::Result<()> {
+        create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        write(dir.join("report.md"), self.to_markdown())
+            .await
+            .context("Failed to write Markdown report")?;
+
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize report")?;
+        write(dir.join("report.json"), json)
+            .await
+            .context("Failed to write JSON report")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_request_ids_of_failed_uploads() {
+        let mut report = ProcessingReport::default();
+        report.fail("assets/gear.rbxm", Some("abc-123"));
+        report.fail("assets/broken.png", None);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json["skipped"],
+            serde_json::json!([
+                { "path": "assets/gear.rbxm", "reason": "failed", "request_id": "abc-123" },
+                { "path": "assets/broken.png", "reason": "failed" },
+            ])
+        );
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("- assets/gear.rbxm (request ID: abc-123)\n"));
+        assert!(markdown.contains("- assets/broken.png\n"));
+    }
+}
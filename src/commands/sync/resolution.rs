@@ -0,0 +1,144 @@
+//! Uploads images drawn at high resolution once per scale they are used at,
+//! so games can pick the variant that suits the device.
+
+use super::config::SyncConfig;
+use crate::asset::{AssetType, ProcessOptions};
+use anyhow::{bail, Context};
+use globset::{Glob, GlobMatcher};
+
+pub struct ResolutionVariants {
+    separator: String,
+    globs: Vec<(String, GlobMatcher, Vec<u32>)>,
+}
+
+impl ResolutionVariants {
+    pub fn new(config: &SyncConfig) -> anyhow::Result<Self> {
+        let mut globs = Vec::new();
+        for (glob, scales) in &config.resolution_variants {
+            if scales.is_empty() || scales.contains(&0) {
+                bail!("Resolution variants for {glob} must be a list of scales above 0");
+            }
+            if (1..scales.len()).any(|index| scales[..index].contains(&scales[index])) {
+                bail!("Resolution variants for {glob} list the same scale more than once");
+            }
+
+            let matcher = Glob::new(glob)
+                .with_context(|| format!("Invalid resolution variants glob {glob}"))?
+                .compile_matcher();
+            globs.push((glob.clone(), matcher, scales.clone()));
+        }
+
+        Ok(Self {
+            separator: config.variant_separator().unwrap_or_default(),
+            globs,
+        })
+    }
+
+    /// The path each asset made from a file is synced under, with how it is
+    /// processed. Images matching a glob are treated as drawn at the largest
+    /// scale, and downscaled for each smaller one. Images matching more than
+    /// one glob are rejected, as there's no telling which scales were meant.
+    pub fn expand(
+        &self,
+        path: &str,
+        fixed_path: &str,
+        ext: &str,
+        options: &ProcessOptions,
+    ) -> anyhow::Result<Vec<(String, ProcessOptions)>> {
+        // Animated images aren't resized, so their variants would all match.
+        let still_image = AssetType::from_extension(ext) == Some(AssetType::Decal)
+            && !matches!(ext, "gif" | "apng");
+
+        let mut matches = self
+            .globs
+            .iter()
+            .filter(|(_, matcher, _)| still_image && matcher.is_match(path));
+        let Some((glob, _, scales)) = matches.next() else {
+            return Ok(vec![(fixed_path.to_string(), *options)]);
+        };
+        if let Some((other, _, _)) = matches.next() {
+            bail!("{fixed_path} matches the resolution variants globs {glob} and {other}, so its scales are ambiguous");
+        }
+
+        let largest = scales.iter().max().copied().unwrap_or(1);
+        Ok(scales
+            .iter()
+            .map(|scale| {
+                (
+                    variant_path(fixed_path, &self.separator, *scale),
+                    ProcessOptions {
+                        image_scale: *scale as f32 / largest as f32,
+                        ..*options
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+/// Names a variant like `ui/gear@2x.png`, so it is grouped with the other
+/// variants of `ui/gear.png` in generated code. Only the last extension is
+/// kept after the scale, so `gear.v2.png` becomes `gear.v2@2x.png`.
+fn variant_path(path: &str, separator: &str, scale: u32) -> String {
+    let name_start = path.rfind('/').map_or(0, |index| index + 1);
+
+    match path[name_start..].rfind('.') {
+        Some(dot) => {
+            let (stem, extension) = path.split_at(name_start + dot);
+            format!("{stem}{separator}{scale}x{extension}")
+        }
+        None => format!("{path}{separator}{scale}x"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn variants(globs: &[(&str, &[u32])]) -> anyhow::Result<ResolutionVariants> {
+        let mut config = SyncConfig::codegen_only(String::new());
+        config.resolution_variants = globs
+            .iter()
+            .map(|(glob, scales)| (glob.to_string(), scales.to_vec()))
+            .collect::<BTreeMap<_, _>>();
+        ResolutionVariants::new(&config)
+    }
+
+    #[test]
+    fn names_variants() {
+        assert_eq!(variant_path("ui/gear.png", "@", 2), "ui/gear@2x.png");
+        assert_eq!(variant_path("gear.png", "_", 1), "gear_1x.png");
+        assert_eq!(variant_path("ui/gear", "@", 2), "ui/gear@2x");
+    }
+
+    #[test]
+    fn names_variants_before_the_last_extension() {
+        assert_eq!(variant_path("ui/gear.v2.png", "@", 2), "ui/gear.v2@2x.png");
+        assert_eq!(variant_path("ui.v2/gear", "@", 2), "ui.v2/gear@2x");
+    }
+
+    #[test]
+    fn rejects_duplicate_scales() {
+        assert!(variants(&[("ui/**", &[1, 2, 1])]).is_err());
+        assert!(variants(&[("ui/**", &[])]).is_err());
+        assert!(variants(&[("ui/**", &[0, 1])]).is_err());
+    }
+
+    #[test]
+    fn rejects_overlapping_globs() {
+        let variants = variants(&[("ui/**", &[1, 2]), ("ui/icons/**", &[1, 3])]).unwrap();
+        let options = SyncConfig::codegen_only(String::new()).process_options();
+
+        let expanded = variants
+            .expand("ui/gear.png", "ui/gear.png", "png", &options)
+            .unwrap();
+        let paths: Vec<_> = expanded.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["ui/gear@1x.png", "ui/gear@2x.png"]);
+        assert_eq!(expanded[0].1.image_scale, 0.5);
+
+        assert!(variants
+            .expand("ui/icons/gear.png", "ui/icons/gear.png", "png", &options)
+            .is_err());
+    }
+}
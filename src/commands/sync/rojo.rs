@@ -0,0 +1,143 @@
+//! A partial Rojo project describing where the generated Luau module is
+//! mounted, so a build can merge it into its own project file.
+
+use super::{codegen::CodegenOptions, config::CodegenConfig, write_if_changed};
+use crate::util::path::relative_to;
+use anyhow::{bail, Context};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+pub struct RojoFragment {
+    /// Where the fragment is written, relative to the project root.
+    path: PathBuf,
+    /// The DataModel path of the generated module, such as
+    /// `ReplicatedStorage/Shared/assets`.
+    instance_path: String,
+}
+
+impl RojoFragment {
+    pub fn from_config(config: &CodegenConfig) -> anyhow::Result<Option<Self>> {
+        match (&config.rojo_fragment, &config.rojo_path) {
+            (Some(path), Some(instance_path)) => Ok(Some(Self {
+                path: PathBuf::from(path),
+                instance_path: instance_path.clone(),
+            })),
+            (Some(_), None) => bail!("codegen.rojo_path must be set to write a Rojo fragment"),
+            (None, _) => Ok(None),
+        }
+    }
+
+    fn to_json(&self, module_path: &str) -> anyhow::Result<Value> {
+        let mut names: Vec<&str> = self
+            .instance_path
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .collect();
+        let name = names.pop().context("codegen.rojo_path must not be empty")?;
+
+        let mut tree = json!({ name: { "$path": module_path } });
+        for parent in names.into_iter().rev() {
+            tree = json!({ parent: tree });
+        }
+        tree["$className"] = json!("DataModel");
+
+        Ok(json!({ "name": name, "tree": tree }))
+    }
+
+    /// The path of the generated module from the fragment, as Rojo resolves
+    /// `$path` relative to the project file it is in.
+    fn module_path(&self, write_dir: &Path, options: &CodegenOptions) -> String {
+        // A folder of split modules is mounted through its init.luau.
+        let module = if options.split_by_folder {
+            write_dir.join(&options.output_name)
+        } else {
+            write_dir.join(format!("{}.luau", options.output_name))
+        };
+        let fragment_dir = self.path.parent().unwrap_or(Path::new(""));
+
+        relative_to(&module, fragment_dir)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Writes the fragment, returning its path if it changed. Nothing is
+    /// written when no Luau module is generated.
+    pub async fn write(
+        &self,
+        write_dir: &Path,
+        options: &CodegenOptions,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        if !options.luau {
+            return Ok(None);
+        }
+
+        let module_path = self.module_path(write_dir, options);
+        let json = serde_json::to_string_pretty(&self.to_json(&module_path)?)?;
+
+        let changed = write_if_changed(&self.path, &format!("{json}\n"))
+            .await
+            .context("Failed to write Rojo fragment")?;
+
+        Ok(changed.then(|| self.path.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragment(path: &str) -> RojoFragment {
+        RojoFragment {
+            path: PathBuf::from(path),
+            instance_path: "ReplicatedStorage/Shared/assets".to_string(),
+        }
+    }
+
+    #[test]
+    fn nests_the_module_under_its_parents() {
+        let json = fragment("assets.project.json")
+            .to_json("src/shared/assets.luau")
+            .unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "name": "assets",
+                "tree": {
+                    "$className": "DataModel",
+                    "ReplicatedStorage": {
+                        "Shared": {
+                            "assets": { "$path": "src/shared/assets.luau" }
+                        }
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn finds_the_module_from_the_fragment() {
+        let mut options = CodegenOptions::new(&CodegenConfig::default());
+        options.output_name = "assets".to_string();
+        let write_dir = Path::new("./src/shared/");
+
+        assert_eq!(
+            fragment("assets.project.json").module_path(write_dir, &options),
+            "src/shared/assets.luau"
+        );
+        assert_eq!(
+            fragment("build/rojo/assets.project.json").module_path(write_dir, &options),
+            "../../src/shared/assets.luau"
+        );
+        assert_eq!(
+            fragment("src/../src/assets.project.json").module_path(write_dir, &options),
+            "shared/assets.luau"
+        );
+
+        options.split_by_folder = true;
+        assert_eq!(
+            fragment("build/assets.project.json").module_path(write_dir, &options),
+            "../src/shared/assets"
+        );
+    }
+}
@@ -1,17 +1,38 @@
-use super::config::{CodegenStyle, CreatorType, ExistingAsset, SyncConfig};
+use super::{
+    codegen::CodegenOptions,
+    config::{CreatorType, ExistingAsset},
+    processing::FileProcessing,
+    report::ProcessingReport,
+    rojo::RojoFragment,
+    trace::Trace,
+    SyncOptions,
+};
 use crate::{
-    cli::{SyncArgs, SyncTarget},
+    asset::{Asset, AssetType},
+    cli::SyncTarget,
+    util::{
+        audio::check_trim_silence, optimize_png::PngOptimization, path::normalize,
+        svg::font_database,
+    },
     LockFile,
 };
-use anyhow::Context;
+use anyhow::{bail, Context};
 use cookie::Cookie;
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use log::warn;
 use rbxcloud::rbx::v1::assets::{AssetCreator, AssetGroupCreator, AssetUserCreator};
 use resvg::usvg::fontdb::Database;
-use std::{collections::HashMap, env, path::PathBuf, sync::Arc};
-use tokio::fs::create_dir_all;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+use tokio::fs::{create_dir_all, read_to_string};
 
-fn add_trailing_slash(path: &str) -> String {
+pub(super) fn add_trailing_slash(path: &str) -> String {
     if !path.ends_with('/') {
         return format!("{}/", path);
     }
@@ -40,41 +61,95 @@ fn get_cookie(arg_cookie: Option<String>) -> Option<String> {
     })
 }
 
+/// Reads the hashes denied by the file in the ASPHALT_DENIED_HASHES
+/// environment variable, which applies to every project on this machine.
+async fn get_global_denied_hashes() -> anyhow::Result<Vec<String>> {
+    let Ok(path) = env::var("ASPHALT_DENIED_HASHES") else {
+        return Ok(Vec::new());
+    };
+
+    let content = read_to_string(&path)
+        .await
+        .with_context(|| format!("Failed to read denied hashes from {path}"))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect())
+}
+
 pub struct SyncState {
     pub asset_dir: PathBuf,
     pub write_dir: PathBuf,
+    pub ts_write_dir: PathBuf,
+    pub extra_output_dirs: Vec<PathBuf>,
+    pub report_dir: Option<PathBuf>,
+    pub commit_message: Option<String>,
+    pub report: ProcessingReport,
     pub exclude_assets_matcher: GlobSet,
+    /// Files Asphalt writes itself, which must not be synced as assets when
+    /// they are inside the asset directory. They are normalized, so they can
+    /// be compared however the config spells them.
+    pub generated_paths: Vec<PathBuf>,
+    pub type_overrides: Vec<(GlobMatcher, AssetType)>,
+    pub denied_hashes: HashSet<String>,
+    pub denied_assets: Vec<String>,
+    pub locked_matcher: GlobSet,
+    pub locked_assets: Vec<String>,
 
     pub api_key: String,
     pub cookie: Option<String>,
     pub target: SyncTarget,
     pub dry_run: bool,
+    pub budget: Option<u64>,
+    pub require_clean_git: bool,
+    pub strict: bool,
+    pub offline: bool,
     pub csrf: Option<String>,
+    pub trace: Option<Trace>,
+    pub emit_node_tree: Option<PathBuf>,
 
     pub creator: AssetCreator,
+    pub upload_prices: BTreeMap<AssetType, u64>,
+    pub optimize_png: Option<PngOptimization>,
+    pub processing: FileProcessing,
+    /// Assets already processed to estimate the spend, keyed by the path they
+    /// are synced under, so they aren't processed again.
+    pub processed_assets: HashMap<String, Asset>,
 
-    pub typescript: bool,
-    pub output_name: String,
-    pub style: CodegenStyle,
-    pub strip_extension: bool,
+    pub codegen: CodegenOptions,
+    pub rojo_fragment: Option<RojoFragment>,
+    pub emit_meta: bool,
 
     pub fontdb: Arc<Database>,
 
     pub existing_lockfile: LockFile,
     pub new_lockfile: LockFile,
 
-    pub existing: HashMap<String, ExistingAsset>,
+    pub existing: BTreeMap<String, ExistingAsset>,
 }
 
 impl SyncState {
-    pub async fn new(
-        args: SyncArgs,
-        config: SyncConfig,
-        existing_lockfile: LockFile,
-    ) -> anyhow::Result<Self> {
-        let api_key = get_api_key(args.api_key)?;
-        let cookie = get_cookie(args.cookie);
-        let target = args.target.unwrap_or(SyncTarget::Cloud);
+    pub async fn new(options: SyncOptions) -> anyhow::Result<Self> {
+        let SyncOptions {
+            config,
+            existing_lockfile,
+            api_key,
+            cookie,
+            target,
+            dry_run,
+            budget,
+            require_clean_git,
+            strict,
+            offline,
+            trace,
+            emit_node_tree,
+        } = options;
+
+        let api_key = get_api_key(api_key)?;
+        let cookie = get_cookie(cookie);
 
         let creator: AssetCreator = match config.creator.creator_type {
             CreatorType::User => AssetCreator::User(AssetUserCreator {
@@ -93,21 +168,56 @@ impl SyncState {
             .context("Failed to create write directory");
         let write_dir = add_trailing_slash(&config.write_dir);
         let write_dir = PathBuf::from(write_dir);
+        let ts_write_dir = config
+            .ts_write_dir
+            .as_deref()
+            .map(|dir| PathBuf::from(add_trailing_slash(dir)))
+            .unwrap_or_else(|| write_dir.clone());
 
-        let output_name = config
-            .codegen
-            .output_name
-            .as_ref()
-            .unwrap_or(&"assets".to_string())
-            .to_string();
+        let extra_output_dirs = config.codegen.extra_output_dirs();
+
+        let mut generated_paths = Vec::new();
+        let mut overlapping_dirs = Vec::new();
+        let mut output_dirs = vec![&write_dir];
+        if ts_write_dir != write_dir {
+            output_dirs.push(&ts_write_dir);
+        }
+        output_dirs.extend(&extra_output_dirs);
+        let normalized_asset_dir = normalize(&asset_dir);
+        for dir in output_dirs {
+            let normalized_dir = normalize(dir);
+            if normalized_dir.starts_with(&normalized_asset_dir) {
+                overlapping_dirs.push(dir.clone());
+                generated_paths.extend(
+                    config
+                        .codegen
+                        .output_paths()
+                        .into_iter()
+                        .map(|path| normalized_dir.join(path)),
+                );
+            }
+        }
+        if let Some(report_dir) = &config.report_dir {
+            generated_paths.push(normalize(Path::new(report_dir)));
+        }
+        if let Some(rojo_fragment) = &config.codegen.rojo_fragment {
+            generated_paths.push(normalize(Path::new(rojo_fragment)));
+        }
 
-        let typescript = config.codegen.typescript.unwrap_or(false);
-        let style = config.codegen.style.unwrap_or(CodegenStyle::Flat);
+        let mut codegen = CodegenOptions::new(&config.codegen);
+        codegen.header = config.codegen_header().await?;
+        let rojo_fragment = RojoFragment::from_config(&config.codegen)?;
+        let emit_meta = config.codegen.emit_meta.unwrap_or(false);
+        codegen.variant_separator = config.variant_separator();
+        codegen.premultiplied_globs = config.premultiply_alpha.clone();
+        let processing = FileProcessing::new(&config)?;
 
-        let strip_extension = config.codegen.strip_extension.unwrap_or(false);
+        if let Some(threshold) = config.trim_silence {
+            check_trim_silence(threshold)?;
+        }
+        let trims_untranscoded = config.trim_silence.is_some() && config.transcode_audio.is_none();
 
-        let mut font_db = Database::new();
-        font_db.load_system_fonts();
+        let font_db = font_database();
 
         let new_lockfile: LockFile = Default::default();
 
@@ -120,28 +230,164 @@ impl SyncState {
         }
         let exclude_assets_matcher = exclude_assets_matcher_builder.build()?;
 
-        Ok(Self {
+        let mut locked_matcher_builder = GlobSetBuilder::new();
+        for glob in config.locked {
+            let glob = Glob::new(&glob)?;
+            locked_matcher_builder.add(glob);
+        }
+        let locked_matcher = locked_matcher_builder.build()?;
+
+        let mut denied_hashes: HashSet<String> = config.denied_hashes.into_iter().collect();
+        denied_hashes.extend(get_global_denied_hashes().await?);
+
+        let mut type_overrides = Vec::new();
+        for (glob, asset_type) in config.type_overrides {
+            let matcher = Glob::new(&glob)?.compile_matcher();
+            type_overrides.push((matcher, asset_type));
+        }
+
+        let state = Self {
             asset_dir,
             write_dir,
+            ts_write_dir,
+            extra_output_dirs,
+            report_dir: config.report_dir.map(PathBuf::from),
+            commit_message: config.commit_message,
+            report: ProcessingReport::default(),
             exclude_assets_matcher,
+            generated_paths,
+            type_overrides,
+            denied_hashes,
+            denied_assets: Vec::new(),
+            locked_matcher,
+            locked_assets: Vec::new(),
             api_key,
             creator,
-            typescript,
-            output_name,
-            style,
-            strip_extension,
+            upload_prices: config.upload_prices,
+            optimize_png: config.optimize_png,
+            processing,
+            processed_assets: HashMap::new(),
+            codegen,
+            rojo_fragment,
+            emit_meta,
             fontdb: Arc::new(font_db),
             existing_lockfile,
             new_lockfile,
             existing: manual,
             cookie,
             target,
-            dry_run: args.dry_run,
+            dry_run,
+            budget,
+            require_clean_git: require_clean_git || config.require_clean_git.unwrap_or(false),
+            strict,
+            offline,
             csrf: None,
-        })
+            trace: trace.map(Trace::new),
+            emit_node_tree,
+        };
+
+        for dir in overlapping_dirs {
+            state.warn(format!(
+                "{} is inside the asset directory, so generated files there will not be synced as assets",
+                dir.display()
+            ))?;
+        }
+        if trims_untranscoded {
+            state.warn(
+                "trim_silence only applies to audio converted by transcode_audio, which isn't set, so no audio will be trimmed",
+            )?;
+        }
+
+        Ok(state)
     }
 
     pub fn update_csrf(&mut self, csrf: Option<String>) {
         self.csrf = csrf;
     }
+
+    /// Records a span in the trace, if one is being written.
+    pub fn trace(&mut self, name: &'static str, started: Instant, path: Option<&str>) {
+        if let Some(trace) = &mut self.trace {
+            trace.record(name, started, path);
+        }
+    }
+
+    pub async fn write_trace(&self) -> anyhow::Result<()> {
+        match &self.trace {
+            Some(trace) => trace.write().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Logs a warning, or fails in strict mode.
+    pub fn warn(&self, message: impl Display) -> anyhow::Result<()> {
+        if self.strict {
+            bail!("{message}");
+        }
+
+        warn!("{message}");
+        Ok(())
+    }
+
+    pub fn type_override(&self, path: &str) -> anyhow::Result<Option<AssetType>> {
+        let mut matched: Option<AssetType> = None;
+
+        for (matcher, asset_type) in &self.type_overrides {
+            if !matcher.is_match(path) {
+                continue;
+            }
+
+            match matched {
+                Some(existing) if existing != *asset_type => {
+                    bail!("Conflicting type overrides ({existing} and {asset_type}) match {path}")
+                }
+                _ => matched = Some(*asset_type),
+            }
+        }
+
+        Ok(matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyncConfig;
+    use std::process;
+
+    async fn new_state(
+        asset_dir: &Path,
+        write_dir: &Path,
+        strict: bool,
+    ) -> anyhow::Result<SyncState> {
+        let mut config = SyncConfig::codegen_only(write_dir.to_str().unwrap().to_string());
+        config.asset_dir = asset_dir.to_str().unwrap().to_string();
+
+        let mut options = SyncOptions::new(config, LockFile::default());
+        options.api_key = Some(String::new());
+        options.strict = strict;
+        SyncState::new(options).await
+    }
+
+    #[tokio::test]
+    async fn finds_output_dirs_inside_the_asset_dir() {
+        let dir = env::temp_dir().join(format!("asphalt-overlap-{}", process::id()));
+        let asset_dir = dir.join("./assets");
+        let write_dir = dir.join("src/../assets/./generated");
+
+        let state = new_state(&asset_dir, &write_dir, false).await.unwrap();
+        assert!(state
+            .generated_paths
+            .contains(&normalize(&dir.join("assets/generated/assets.luau"))));
+
+        let error = new_state(&asset_dir, &write_dir, true).await.err().unwrap();
+        assert!(error.to_string().contains("is inside the asset directory"));
+
+        let state = new_state(&asset_dir, &dir.join("assets/../src"), true)
+            .await
+            .unwrap();
+        assert!(state.generated_paths.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,76 @@
+//! Records how long each stage of a sync takes, written in the Chrome trace
+//! event format so it can be opened in Perfetto or `chrome://tracing`.
+
+use anyhow::Context;
+use serde::Serialize;
+use std::{collections::BTreeMap, path::PathBuf, time::Instant};
+use tokio::fs::write;
+
+#[derive(Debug, Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    cat: &'static str,
+    /// Always `X`, a complete event with a duration.
+    ph: &'static str,
+    /// When the span started, in microseconds since the sync started.
+    ts: u128,
+    dur: u128,
+    pid: u32,
+    tid: u32,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    args: BTreeMap<&'static str, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceFile<'a> {
+    trace_events: &'a [TraceEvent],
+    display_time_unit: &'static str,
+}
+
+pub struct Trace {
+    path: PathBuf,
+    start: Instant,
+    events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Records a span that started at `started` and ends now, optionally
+    /// for the file at `path`.
+    pub fn record(&mut self, name: &'static str, started: Instant, path: Option<&str>) {
+        let mut args = BTreeMap::new();
+        if let Some(path) = path {
+            args.insert("path", path.to_string());
+        }
+
+        self.events.push(TraceEvent {
+            name,
+            cat: "asphalt",
+            ph: "X",
+            ts: started.duration_since(self.start).as_micros(),
+            dur: started.elapsed().as_micros(),
+            pid: 1,
+            tid: 1,
+            args,
+        });
+    }
+
+    pub async fn write(&self) -> anyhow::Result<()> {
+        let json = serde_json::to_string(&TraceFile {
+            trace_events: &self.events,
+            display_time_unit: "ms",
+        })?;
+
+        write(&self.path, json)
+            .await
+            .with_context(|| format!("Failed to write trace to {}", self.path.display()))
+    }
+}
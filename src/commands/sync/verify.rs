@@ -0,0 +1,258 @@
+//! Checks that the lockfile and generated code still match the files they
+//! were made from, to catch hand edits to either.
+
+use super::{
+    codegen::{generate_files, CodegenOptions},
+    config::SyncConfig,
+    fix_path, generated_file_paths,
+    processing::FileProcessing,
+    LockfileCodegen,
+};
+use crate::{
+    asset::{Asset, AssetType},
+    util::{flipbook::Flipbook, svg::font_database},
+    LockFile,
+};
+use anyhow::{bail, Context};
+use globset::{Glob, GlobSetBuilder};
+use log::{info, warn};
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::fs::{read, read_to_string};
+use walkdir::WalkDir;
+
+/// What was learned about a local file by processing it.
+struct LocalAsset {
+    hash: String,
+    dimensions: Option<(u32, u32)>,
+    size: usize,
+    flipbook: Option<Flipbook>,
+}
+
+/// Processes every asset in the asset directory, keyed by its path.
+async fn hash_local_assets(
+    config: &SyncConfig,
+    asset_dir: &str,
+) -> anyhow::Result<BTreeMap<String, LocalAsset>> {
+    let mut exclude_builder = GlobSetBuilder::new();
+    for glob in &config.exclude_assets {
+        exclude_builder.add(Glob::new(glob)?);
+    }
+    let exclude_matcher = exclude_builder.build()?;
+
+    let mut type_overrides = Vec::new();
+    for (glob, asset_type) in &config.type_overrides {
+        type_overrides.push((Glob::new(glob)?.compile_matcher(), *asset_type));
+    }
+
+    let font_db = Arc::new(font_database());
+    let processing = FileProcessing::new(config)?;
+
+    let mut local = BTreeMap::new();
+
+    for entry in WalkDir::new(asset_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.path().is_file())
+    {
+        let path = entry.path();
+        let Some(path_str) = path.to_str() else {
+            continue;
+        };
+        if exclude_matcher.is_match(path) {
+            continue;
+        }
+
+        let type_override = type_overrides
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(path_str))
+            .map(|(_, asset_type)| *asset_type);
+        let Some(ext) = path.extension().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if type_override.is_none() && AssetType::from_extension(ext).is_none() {
+            continue;
+        }
+
+        let fixed_path = fix_path(path_str);
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let data = read(path)
+            .await
+            .with_context(|| format!("Failed to read {fixed_path}"))?;
+        let variants = processing.assets(path_str, &fixed_path, ext)?;
+        for (asset_path, process_options) in variants {
+            let mut asset = Asset::new(
+                file_name.clone(),
+                data.clone(),
+                ext,
+                font_db.clone(),
+                type_override,
+                &process_options,
+            )
+            .await
+            .with_context(|| format!("Failed to process {asset_path}"))?;
+            if let Some(optimization) = config.optimize_png {
+                asset
+                    .optimize(optimization)
+                    .with_context(|| format!("Failed to optimize {asset_path}"))?;
+            }
+
+            local.insert(
+                asset_path,
+                LocalAsset {
+                    hash: asset.hash(),
+                    dimensions: asset.processing().dimensions,
+                    size: asset.processing().size,
+                    flipbook: asset.flipbook().cloned(),
+                },
+            );
+        }
+    }
+
+    Ok(local)
+}
+
+/// Whether generated code includes anything only known by processing assets.
+fn needs_local_assets(codegen: &LockfileCodegen) -> bool {
+    let options = &codegen.options;
+    let animated = codegen
+        .assets
+        .keys()
+        .any(|path| path.ends_with(".gif") || path.ends_with(".apng"));
+
+    options.include_dimensions || !options.bundles.is_empty() || animated
+}
+
+/// Adds what processing local assets revealed to the options.
+fn add_local_assets(options: &mut CodegenOptions, local_assets: BTreeMap<String, LocalAsset>) {
+    for (path, asset) in local_assets {
+        if let Some(dimensions) = asset.dimensions {
+            options.dimensions.insert(path.clone(), dimensions);
+        }
+        if let Some(flipbook) = asset.flipbook {
+            options.flipbooks.insert(path.clone(), flipbook);
+        }
+        options.sizes.insert(path, asset.size);
+    }
+}
+
+/// Describes where a generated file first differs from what it should be.
+fn describe_difference(existing: &str, expected: &str) -> String {
+    let line = existing
+        .lines()
+        .zip(expected.lines())
+        .position(|(existing, expected)| existing != expected)
+        .unwrap_or_else(|| existing.lines().count().min(expected.lines().count()))
+        + 1;
+
+    format!(
+        "first differs at line {line}, and has {} lines where {} were expected",
+        existing.lines().count(),
+        expected.lines().count()
+    )
+}
+
+/// Regenerates code in memory, returning a description of every generated
+/// file on disk that doesn't match it.
+async fn stale_generated_files(codegen: &LockfileCodegen) -> anyhow::Result<Vec<String>> {
+    let generated = generate_files(&codegen.assets, &codegen.asset_dir, &codegen.options)?;
+    let mut stale = Vec::new();
+
+    for (path, contents) in &generated {
+        for path in generated_file_paths(
+            &codegen.write_dir,
+            &codegen.ts_write_dir,
+            &codegen.extra_output_dirs,
+            path,
+        ) {
+            match read_to_string(&path).await {
+                Ok(existing) if existing == *contents => {}
+                Ok(existing) => stale.push(format!(
+                    "{} {}",
+                    path.display(),
+                    describe_difference(&existing, contents)
+                )),
+                Err(_) => stale.push(format!("{} has not been generated", path.display())),
+            }
+        }
+    }
+
+    Ok(stale)
+}
+
+/// Fails if the generated code on disk is not what the lockfile would
+/// generate, without syncing or contacting Roblox.
+pub async fn check_codegen(config: SyncConfig, lockfile: LockFile) -> anyhow::Result<()> {
+    if config.codegen.emit_meta.unwrap_or(false) {
+        bail!("Generated code includes the time of the last sync, so it cannot be checked");
+    }
+
+    let mut codegen = LockfileCodegen::new(&config, &lockfile).await?;
+    if needs_local_assets(&codegen) {
+        let local_assets = hash_local_assets(&config, &codegen.asset_dir).await?;
+        add_local_assets(&mut codegen.options, local_assets);
+    }
+
+    let stale = stale_generated_files(&codegen).await?;
+    if !stale.is_empty() {
+        bail!("Generated code is out of date:\n{}", stale.join("\n"));
+    }
+
+    info!("Generated code is up to date");
+    Ok(())
+}
+
+/// Regenerates code from the lockfile and compares it with the generated
+/// files on disk. With `local`, every file in the lockfile is also processed
+/// again to check that its hash still matches.
+pub async fn verify(config: SyncConfig, lockfile: LockFile, local: bool) -> anyhow::Result<()> {
+    let mut codegen = LockfileCodegen::new(&config, &lockfile).await?;
+
+    let mut problems = Vec::new();
+
+    if local {
+        let local_assets = hash_local_assets(&config, &codegen.asset_dir).await?;
+
+        for (path, entry) in &lockfile.entries {
+            match local_assets.get(path) {
+                Some(asset) if asset.hash != entry.hash => {
+                    problems.push(format!("{path} has changed since it was last synced"))
+                }
+                Some(_) => {}
+                None => problems.push(format!("{path} is in the lockfile, but not on disk")),
+            }
+        }
+
+        let unsynced = local_assets
+            .keys()
+            .filter(|path| !lockfile.entries.contains_key(*path))
+            .count();
+        if unsynced > 0 {
+            info!(
+                "{unsynced} file{} not been synced yet",
+                if unsynced == 1 { " has" } else { "s have" }
+            );
+        }
+
+        add_local_assets(&mut codegen.options, local_assets);
+    }
+
+    if config.codegen.emit_meta.unwrap_or(false) {
+        warn!("Generated files include the time of the last sync, so they were not verified");
+    } else if needs_local_assets(&codegen) && !local {
+        warn!("Generated files include image dimensions, sizes, or flipbooks, which are only known with --local, so they were not verified");
+    } else {
+        problems.extend(stale_generated_files(&codegen).await?);
+    }
+
+    if !problems.is_empty() {
+        bail!("Verification failed:\n{}", problems.join("\n"));
+    }
+
+    info!(
+        "Verified {} asset{}",
+        lockfile.entries.len(),
+        if lockfile.entries.len() == 1 { "" } else { "s" }
+    );
+
+    Ok(())
+}
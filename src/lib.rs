@@ -0,0 +1,31 @@
+//! Asphalt uploads assets to Roblox and generates code to reference them.
+//!
+//! The `asphalt` command line tool is a thin wrapper around this library, so
+//! build tools can run a sync in-process instead of shelling out:
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use asphalt::{LockFile, SyncConfig, SyncOptions};
+//!
+//! let config = SyncConfig::read().await?;
+//! let lockfile = LockFile::read().await?;
+//!
+//! let output = asphalt::sync(SyncOptions::new(config, lockfile)).await?;
+//! for (path, asset_id) in &output.uploaded {
+//!     println!("Uploaded {path} as {asset_id}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod asset;
+pub mod cli;
+pub mod commands;
+pub mod lockfile;
+pub mod upload;
+pub mod util;
+
+pub use commands::sync::{
+    check_codegen, config::SyncConfig, generate, sync, verify, SyncOptions, SyncOutput,
+};
+pub use lockfile::{FileEntry, LockFile, LockFileDelta};
@@ -1,11 +1,16 @@
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, path::Path};
 use tokio::fs::{read_to_string, write};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct FileEntry {
     pub hash: String,
     pub asset_id: u64,
+    /// Written by hand to explain an entry, and kept when the lockfile is
+    /// rewritten.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -13,6 +18,20 @@ pub struct LockFile {
     pub entries: BTreeMap<String, FileEntry>,
 }
 
+/// The entries that differ between two lockfiles.
+#[derive(Debug, Default)]
+pub struct LockFileDelta {
+    pub added: BTreeMap<String, FileEntry>,
+    pub changed: BTreeMap<String, FileEntry>,
+    pub removed: Vec<String>,
+}
+
+impl LockFileDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
 pub static FILE_NAME: &str = "asphalt.lock.toml";
 
 impl LockFile {
@@ -24,10 +43,44 @@ impl LockFile {
         }
     }
 
+    /// Reads a lockfile from another path, such as one from an older
+    /// release. Unlike [`LockFile::read`], it must exist.
+    pub async fn read_from(path: &Path) -> anyhow::Result<Self> {
+        let content = read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(toml::from_str(&content)?)
+    }
+
     pub async fn write(&self, filename: &Path) -> anyhow::Result<()> {
         let content = toml::to_string(self)?;
         write(filename, content).await?;
 
         Ok(())
     }
+
+    pub fn delta(&self, previous: &LockFile) -> LockFileDelta {
+        let mut delta = LockFileDelta::default();
+
+        for (path, entry) in &self.entries {
+            match previous.entries.get(path) {
+                None => {
+                    delta.added.insert(path.clone(), entry.clone());
+                }
+                Some(previous_entry) if previous_entry != entry => {
+                    delta.changed.insert(path.clone(), entry.clone());
+                }
+                Some(_) => {}
+            }
+        }
+
+        delta.removed = previous
+            .entries
+            .keys()
+            .filter(|path| !self.entries.contains_key(*path))
+            .cloned()
+            .collect();
+
+        delta
+    }
 }
@@ -1,18 +1,17 @@
 use anyhow::Context;
+use asphalt::{
+    check_codegen,
+    cli::{Cli, Commands},
+    commands::{
+        export::export, init::init, list::list, migrate_tarmac_manifest::migrate_manifest,
+        new::new_project, snapshot::snapshot, sync::read_key_mapping,
+    },
+    generate, sync, verify, LockFile, SyncConfig, SyncOptions,
+};
 use clap::Parser;
-use cli::{Cli, Commands};
-use commands::{init::init, list::list, sync::sync};
 use dotenv::dotenv;
-pub use lockfile::{FileEntry, LockFile};
 use log::LevelFilter;
 
-pub mod asset;
-pub mod cli;
-mod commands;
-pub mod lockfile;
-pub mod upload;
-pub mod util;
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
@@ -29,15 +28,70 @@ async fn main() -> anyhow::Result<()> {
     let existing_lockfile = LockFile::read().await.context("Failed to read lockfile")?;
 
     match args.command {
-        Commands::Sync(sync_args) => sync(sync_args, existing_lockfile)
-            .await
-            .context("Failed to sync"),
-        Commands::List => list(existing_lockfile).await.context("Failed to list"),
-        Commands::Init => init().await.context("Failed to initialize"),
-        Commands::MigrateTarmacManifest(args) => {
-            commands::migrate_tarmac_manifest::migrate_manifest(args)
+        Commands::Sync(sync_args) => {
+            let config = SyncConfig::read().await.context("Failed to read config")?;
+
+            if sync_args.check_codegen {
+                return check_codegen(config, existing_lockfile)
+                    .await
+                    .context("Failed to check generated code");
+            }
+
+            let print_changed = sync_args.print_changed;
+            let options =
+                SyncOptions::from_args(sync_args, config, existing_lockfile, args.offline);
+
+            let output = sync(options).await.context("Failed to sync")?;
+            if print_changed {
+                for path in output.changed_files {
+                    println!("{}", path.display());
+                }
+            }
+
+            Ok(())
+        }
+        Commands::Codegen(codegen_args) => {
+            let lockfile = match &codegen_args.lockfile {
+                Some(path) => LockFile::read_from(path).await?,
+                None => existing_lockfile,
+            };
+            let keys = match &codegen_args.keys {
+                Some(path) => Some(read_key_mapping(path).await?),
+                None => None,
+            };
+
+            let mut config = match SyncConfig::read().await {
+                Ok(config) => config,
+                // Assets can be named without a project when keys are given.
+                Err(_) if keys.is_some() => SyncConfig::codegen_only(".".to_string()),
+                Err(e) => return Err(e.context("Failed to read config")),
+            };
+            if let Some(write_dir) = codegen_args.write_dir {
+                config.write_dir = write_dir;
+                config.ts_write_dir = None;
+            }
+
+            generate(config, lockfile, codegen_args.lang, keys)
+                .await
+                .map(|_| ())
+                .context("Failed to generate code")
+        }
+        Commands::Verify(args) => {
+            let config = SyncConfig::read().await.context("Failed to read config")?;
+
+            verify(config, existing_lockfile, args.local)
                 .await
-                .context("Failed to migrate tarmac-manifest.toml")
+                .context("Failed to verify")
         }
+        Commands::List => list(existing_lockfile).await.context("Failed to list"),
+        Commands::Init => init().await.context("Failed to initialize"),
+        Commands::New(args) => new_project(args).await.context("Failed to create project"),
+        Commands::MigrateTarmacManifest(args) => migrate_manifest(args)
+            .await
+            .context("Failed to migrate tarmac-manifest.toml"),
+        Commands::Snapshot(args) => snapshot(args).await.context("Failed to snapshot"),
+        Commands::Export(args) => export(args, existing_lockfile)
+            .await
+            .context("Failed to export"),
     }
 }
@@ -5,10 +5,10 @@ use rbxcloud::rbx::v1::assets::{
     create_asset_with_contents, get_asset, AssetCreation, AssetCreationContext, AssetCreator,
     AssetType, CreateAssetParamsWithContents, GetAssetParams,
 };
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde::Deserialize;
 use serde_xml_rs::from_str;
-use std::time::Duration;
+use std::{fmt, time::Duration};
 use tokio::time::sleep;
 
 #[derive(Deserialize, Debug)]
@@ -36,8 +36,94 @@ struct Content {
 
 const ASSET_DESCRIPTION: &str = "Uploaded by Asphalt";
 
+const USER_AGENT: &str = concat!("asphalt/", env!("CARGO_PKG_VERSION"));
+
+/// Headers Roblox uses to identify a request, which are useful to include in
+/// support tickets.
+const REQUEST_ID_HEADERS: &[&str] = &["x-roblox-request-id", "x-request-id"];
+
+/// Returned instead of contacting Roblox while offline.
+#[derive(Debug)]
+pub struct OfflineError {
+    action: &'static str,
+}
+
+impl fmt::Display for OfflineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tried to {} while offline, remove --offline to allow network requests",
+            self.action
+        )
+    }
+}
+
+impl std::error::Error for OfflineError {}
+
+/// Whether an error was caused by trying to contact Roblox while offline,
+/// which should stop a sync rather than skip a single file.
+pub fn is_offline_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<OfflineError>())
+}
+
+/// Fails instead of contacting Roblox if the run is offline. Every request is
+/// made by the cloud backend, which checks this before each upload.
+pub fn ensure_online(offline: bool, action: &'static str) -> Result<(), OfflineError> {
+    if offline {
+        return Err(OfflineError { action });
+    }
+
+    Ok(())
+}
+
+fn client() -> anyhow::Result<Client> {
+    Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+fn request_id(response: &Response) -> Option<String> {
+    REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|name| response.headers().get(*name))
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// A request to Roblox that failed, with the ID Roblox gave it, which is
+/// kept so it can be included in the sync report.
+#[derive(Debug)]
+pub struct RobloxError {
+    message: &'static str,
+    request_id: Option<String>,
+}
+
+impl fmt::Display for RobloxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.request_id {
+            Some(id) => write!(f, "{} (request ID: {id})", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for RobloxError {}
+
+/// The ID of the Roblox request an error came from, if it has one.
+pub fn error_request_id(error: &anyhow::Error) -> Option<&str> {
+    error.downcast_ref::<RobloxError>()?.request_id.as_deref()
+}
+
+fn with_request_id(message: &'static str, request_id: &Option<String>) -> RobloxError {
+    RobloxError {
+        message,
+        request_id: request_id.clone(),
+    }
+}
+
 async fn get_image_id(asset_id: u64) -> anyhow::Result<u64> {
-    let client = Client::new();
+    let client = client()?;
     let url = format!("https://assetdelivery.roblox.com/v1/asset?id={}", asset_id);
 
     let response = client
@@ -45,14 +131,19 @@ async fn get_image_id(asset_id: u64) -> anyhow::Result<u64> {
         .send()
         .await
         .context("Failed to get image ID")?;
+    let request_id = request_id(&response);
 
     let body = response
         .text()
         .await
-        .context("Failed to parse request body to text")?;
+        .with_context(|| with_request_id("Failed to parse request body to text", &request_id))?;
 
-    let roblox: Roblox =
-        from_str(&body).context("Failed to parse request body to Roblox XML format")?;
+    let roblox: Roblox = from_str(&body).with_context(|| {
+        with_request_id(
+            "Failed to parse request body to Roblox XML format",
+            &request_id,
+        )
+    })?;
 
     let id_str = roblox
         .item
@@ -66,12 +157,16 @@ async fn get_image_id(asset_id: u64) -> anyhow::Result<u64> {
     id_str.parse::<u64>().context("Failed to parse image ID")
 }
 
+/// Uploads an asset with Open Cloud. Its requests are made by rbxcloud with
+/// its own client, so they don't send our user agent, and their request IDs
+/// aren't available to include in errors.
 pub async fn upload_cloud_asset(
     contents: Vec<u8>,
     display_name: String,
     asset_type: AssetType,
     api_key: String,
     creator: AssetCreator,
+    expected_price: Option<u64>,
 ) -> anyhow::Result<u64> {
     let create_params = CreateAssetParamsWithContents {
         contents: &contents,
@@ -81,7 +176,7 @@ pub async fn upload_cloud_asset(
             display_name,
             creation_context: AssetCreationContext {
                 creator,
-                expected_price: None,
+                expected_price,
             },
             description: ASSET_DESCRIPTION.to_string(),
         },
@@ -144,7 +239,7 @@ pub struct AnimationResult {
 }
 
 pub async fn get_csrf_token(cookie: String) -> anyhow::Result<String> {
-    let client = Client::new();
+    let client = client()?;
 
     let response = client
         .post(ANIMATION_URL)
@@ -154,11 +249,12 @@ pub async fn get_csrf_token(cookie: String) -> anyhow::Result<String> {
         .send()
         .await
         .context("Failed to get CSRF token")?;
+    let request_id = request_id(&response);
 
     let csrf = response
         .headers()
         .get("x-csrf-token")
-        .context("Failed to get CSRF token header")?
+        .with_context(|| with_request_id("Failed to get CSRF token header", &request_id))?
         .to_str()
         .context("Failed to convert CSRF token header to string")?;
 
@@ -172,7 +268,7 @@ pub async fn upload_animation(
     csrf: Option<String>,
     creator: AssetCreator,
 ) -> anyhow::Result<AnimationResult> {
-    let client = Client::new();
+    let client = client()?;
 
     let csrf = if let Some(token) = csrf {
         token
@@ -208,18 +304,54 @@ pub async fn upload_animation(
         .body(contents)
         .send()
         .await
-        .context("Failed to send animation upload request")?
+        .context("Failed to send animation upload request")?;
+    let request_id = request_id(&response);
+
+    let response = response
         .error_for_status()
-        .context("Failed to upload animation")?;
+        .with_context(|| with_request_id("Failed to upload animation", &request_id))?;
 
     let body = response
         .text()
         .await
-        .context("Failed to parse request body to text")?;
+        .with_context(|| with_request_id("Failed to parse request body to text", &request_id))?;
 
     let id = body
         .parse::<u64>()
-        .context("Failed to parse animation ID")?;
+        .with_context(|| with_request_id("Failed to parse animation ID", &request_id))?;
 
     Ok(AnimationResult { asset_id: id, csrf })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_request_ids_through_context() {
+        let request_id = Some("abc-123".to_string());
+        let error = "x"
+            .parse::<u64>()
+            .with_context(|| with_request_id("Failed to parse animation ID", &request_id))
+            .context("Failed to upload gear.rbxm")
+            .unwrap_err();
+
+        assert_eq!(error_request_id(&error), Some("abc-123"));
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to upload gear.rbxm: Failed to parse animation ID (request ID: abc-123): invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn has_no_request_id_without_a_response() {
+        let error = anyhow::anyhow!("Cookie required for uploading animations");
+        assert_eq!(error_request_id(&error), None);
+
+        let error = "x"
+            .parse::<u64>()
+            .with_context(|| with_request_id("Failed to parse animation ID", &None))
+            .unwrap_err();
+        assert_eq!(error_request_id(&error), None);
+    }
+}
@@ -0,0 +1,106 @@
+//! Converts audio Roblox doesn't accept with ffmpeg, which has to be
+//! installed separately.
+
+use anyhow::{bail, Context};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+/// Vorbis quality from 0 to 10, where 5 is transparent for most sounds.
+const OGG_QUALITY: &str = "5";
+
+/// Checks that a silence threshold is a level in decibels below full scale.
+pub fn check_trim_silence(threshold: f32) -> anyhow::Result<()> {
+    if !threshold.is_finite() || threshold >= 0.0 {
+        bail!("trim_silence must be a threshold below 0 dB, not {threshold}");
+    }
+
+    Ok(())
+}
+
+/// An ffmpeg filter that removes silence quieter than `threshold` decibels
+/// from the start of a sound, and then, by reversing it, from the end.
+fn trim_silence_filter(threshold: f32) -> anyhow::Result<String> {
+    check_trim_silence(threshold)?;
+
+    let trim_start = format!("silenceremove=start_periods=1:start_threshold={threshold}dB");
+    Ok(format!("{trim_start},areverse,{trim_start},areverse"))
+}
+
+/// Transcodes a WAV or FLAC file to Ogg Vorbis, optionally trimming silence
+/// quieter than a threshold in decibels from either end.
+pub fn transcode_to_ogg(data: &[u8], trim_silence: Option<f32>) -> anyhow::Result<Vec<u8>> {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-hide_banner", "-loglevel", "error", "-i", "pipe:0"]);
+    if let Some(threshold) = trim_silence {
+        command.args(["-af", &trim_silence_filter(threshold)?]);
+    }
+
+    let mut child = command
+        .args(["-map_metadata", "-1"])
+        .args(["-c:a", "libvorbis", "-q:a", OGG_QUALITY])
+        .args(["-f", "ogg", "pipe:1"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run ffmpeg, which is needed to transcode audio")?;
+
+    // Written from another thread, so ffmpeg can't block on a full stdout
+    // pipe while its input is still being written.
+    let mut stdin = child
+        .stdin
+        .take()
+        .context("Failed to open ffmpeg's input")?;
+    let input = data.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child
+        .wait_with_output()
+        .context("Failed to wait for ffmpeg")?;
+    // ffmpeg may stop reading early if the input is invalid, which its own
+    // error explains better.
+    let written = writer.join();
+
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    if !matches!(written, Ok(Ok(()))) {
+        bail!("Failed to write audio to ffmpeg");
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_both_ends() {
+        assert_eq!(
+            trim_silence_filter(-60.0).unwrap(),
+            "silenceremove=start_periods=1:start_threshold=-60dB,areverse,\
+             silenceremove=start_periods=1:start_threshold=-60dB,areverse"
+        );
+        assert_eq!(
+            trim_silence_filter(-42.5).unwrap(),
+            "silenceremove=start_periods=1:start_threshold=-42.5dB,areverse,\
+             silenceremove=start_periods=1:start_threshold=-42.5dB,areverse"
+        );
+    }
+
+    #[test]
+    fn rejects_thresholds_at_or_above_full_scale() {
+        assert!(check_trim_silence(-0.1).is_ok());
+        assert!(check_trim_silence(0.0).is_err());
+        assert!(check_trim_silence(6.0).is_err());
+        assert!(check_trim_silence(f32::NAN).is_err());
+        assert!(check_trim_silence(f32::NEG_INFINITY).is_err());
+    }
+}
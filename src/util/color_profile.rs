@@ -0,0 +1,173 @@
+//! Converts images tagged with a colour profile other than sRGB, as Roblox
+//! drops profiles and shows every image as if it were sRGB.
+
+use anyhow::Context;
+use image::{DynamicImage, ImageBuffer};
+use moxcms::{ColorProfile, DataColorSpace, Layout, ProfileText, TransformOptions};
+
+fn description(profile: &ColorProfile) -> Option<String> {
+    match profile.description.as_ref()? {
+        ProfileText::PlainString(text) => Some(text.clone()),
+        ProfileText::Localizable(texts) => texts.first().map(|text| text.value.clone()),
+        ProfileText::Description(text) => Some(text.ascii_string.clone()),
+    }
+    .map(|text| text.trim_end_matches('\0').trim().to_string())
+    .filter(|text| !text.is_empty())
+}
+
+/// Whether a profile is sRGB in all but name, as descriptions are free text
+/// and the many sRGB profiles differ slightly in how they are encoded. It is
+/// if converting a grid of colours from it changes none by more than one.
+fn is_srgb(profile: &ColorProfile, srgb: &ColorProfile) -> anyhow::Result<bool> {
+    let steps = [0u8, 51, 102, 153, 204, 255];
+    let mut source = Vec::new();
+    for red in steps {
+        for green in steps {
+            for blue in steps {
+                source.extend([red, green, blue]);
+            }
+        }
+    }
+
+    let mut converted = vec![0; source.len()];
+    profile
+        .create_transform_8bit(Layout::Rgb, srgb, Layout::Rgb, TransformOptions::default())?
+        .transform(&source, &mut converted)?;
+
+    Ok(source
+        .iter()
+        .zip(&converted)
+        .all(|(before, after)| before.abs_diff(*after) <= 1))
+}
+
+/// Converts an image from its embedded ICC profile to sRGB, returning the
+/// name of the profile it was converted from. Images that are already sRGB,
+/// or aren't RGB, are left alone.
+pub fn convert_to_srgb(image: &mut DynamicImage, icc: &[u8]) -> anyhow::Result<Option<String>> {
+    let profile = ColorProfile::new_from_slice(icc).context("Failed to read colour profile")?;
+    if profile.color_space != DataColorSpace::Rgb {
+        return Ok(None);
+    }
+
+    let srgb = ColorProfile::new_srgb();
+    if is_srgb(&profile, &srgb)? {
+        return Ok(None);
+    }
+
+    let name = description(&profile).unwrap_or_else(|| "an unnamed profile".to_string());
+    let (width, height) = (image.width(), image.height());
+    let has_alpha = image.color().has_alpha();
+    let layout = if has_alpha { Layout::Rgba } else { Layout::Rgb };
+    let wide = image.color().bytes_per_pixel() > image.color().channel_count();
+
+    let converted = if wide {
+        let source = if has_alpha {
+            image.to_rgba16().into_raw()
+        } else {
+            image.to_rgb16().into_raw()
+        };
+        let mut pixels = vec![0; source.len()];
+        profile
+            .create_transform_16bit(layout, &srgb, layout, TransformOptions::default())?
+            .transform(&source, &mut pixels)?;
+
+        if has_alpha {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba16)
+        } else {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb16)
+        }
+    } else {
+        let source = if has_alpha {
+            image.to_rgba8().into_raw()
+        } else {
+            image.to_rgb8().into_raw()
+        };
+        let mut pixels = vec![0; source.len()];
+        profile
+            .create_transform_8bit(layout, &srgb, layout, TransformOptions::default())?
+            .transform(&source, &mut pixels)?;
+
+        if has_alpha {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8)
+        } else {
+            ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+        }
+    };
+
+    *image = converted.context("Converted image has the wrong number of pixels")?;
+    Ok(Some(name))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::convert_to_srgb;
+    use flate2::{write::ZlibEncoder, Compression};
+    use image::{DynamicImage, ImageDecoder, ImageReader};
+    use moxcms::ColorProfile;
+    use std::io::{Cursor, Write};
+
+    /// A 2x2 PNG of one colour, tagged with a profile. The iCCP chunk is
+    /// written by hand, as not every version of png writes one.
+    pub(crate) fn tagged_png(color: [u8; 3], icc: &[u8]) -> Vec<u8> {
+        let mut profile_data = ZlibEncoder::new(Vec::new(), Compression::default());
+        profile_data.write_all(icc).unwrap();
+        let mut iccp = b"profile\0\0".to_vec();
+        iccp.extend(profile_data.finish().unwrap());
+
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, 2, 2);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_chunk(png::chunk::iCCP, &iccp).unwrap();
+        writer.write_image_data(&color.repeat(4)).unwrap();
+        writer.finish().unwrap();
+
+        data
+    }
+
+    fn decode(data: &[u8]) -> (DynamicImage, Vec<u8>) {
+        let mut decoder = ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .unwrap()
+            .into_decoder()
+            .unwrap();
+        let icc = decoder.icc_profile().unwrap().unwrap();
+        (DynamicImage::from_decoder(decoder).unwrap(), icc)
+    }
+
+    #[test]
+    fn converts_display_p3() {
+        let (mut image, icc) = decode(&tagged_png(
+            [200, 100, 50],
+            &ColorProfile::new_display_p3().encode().unwrap(),
+        ));
+
+        let profile = convert_to_srgb(&mut image, &icc).unwrap();
+        assert_eq!(profile.as_deref(), Some("Display P3"));
+
+        let pixel = image.to_rgb8().get_pixel(0, 0).0;
+        for (channel, expected) in pixel.iter().zip([215u8, 93, 31]) {
+            assert!(channel.abs_diff(expected) <= 1, "{pixel:?}");
+        }
+    }
+
+    #[test]
+    fn leaves_srgb_alone() {
+        let (mut image, icc) = decode(&tagged_png(
+            [200, 100, 50],
+            &ColorProfile::new_srgb().encode().unwrap(),
+        ));
+
+        assert_eq!(convert_to_srgb(&mut image, &icc).unwrap(), None);
+        assert_eq!(image.to_rgb8().get_pixel(0, 0).0, [200, 100, 50]);
+    }
+
+    #[test]
+    fn rejects_unreadable_profiles() {
+        let mut image = DynamicImage::new_rgb8(2, 2);
+
+        assert!(convert_to_srgb(&mut image, b"not a profile").is_err());
+    }
+}
@@ -0,0 +1,463 @@
+//! Reads enough of binary FBX files to catch meshes Roblox would reject,
+//! before they use up an upload.
+
+use anyhow::{bail, Context};
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+const BINARY_MAGIC: &[u8] = b"Kaydara FBX Binary  \0";
+
+/// The most triangles Roblox accepts in a single mesh.
+pub const MAX_TRIANGLES: u64 = 20_000;
+
+/// How deeply nodes can be nested before a file is treated as corrupt.
+const MAX_DEPTH: usize = 64;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .context("FBX file is truncated")?;
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
+
+/// A property of a node, keeping only the kinds needed to count triangles.
+enum Property {
+    String(String),
+    IntArray(Vec<i32>),
+    Other,
+}
+
+struct Node {
+    name: String,
+    properties: Vec<Property>,
+    children: Vec<Node>,
+}
+
+fn read_array(reader: &mut Reader, element_size: usize) -> anyhow::Result<Vec<u8>> {
+    let length = reader.u32()? as usize;
+    let encoding = reader.u32()?;
+    let compressed_length = reader.u32()? as usize;
+    let bytes = reader.take(compressed_length)?;
+
+    let size = length
+        .checked_mul(element_size)
+        .context("FBX array is too large")?;
+    let data = match encoding {
+        0 => bytes.to_vec(),
+        1 => {
+            // The length comes from the file, so it is only trusted as far as
+            // the data goes.
+            let mut data = Vec::new();
+            ZlibDecoder::new(bytes)
+                .take(size as u64 + 1)
+                .read_to_end(&mut data)
+                .context("Failed to decompress FBX array")?;
+            data
+        }
+        _ => bail!("FBX array has an unknown encoding {encoding}"),
+    };
+
+    if data.len() != size {
+        bail!("FBX array is the wrong length");
+    }
+    Ok(data)
+}
+
+fn read_property(reader: &mut Reader) -> anyhow::Result<Property> {
+    let property = match reader.u8()? {
+        b'Y' => {
+            reader.take(2)?;
+            Property::Other
+        }
+        b'C' => {
+            reader.take(1)?;
+            Property::Other
+        }
+        b'I' | b'F' => {
+            reader.take(4)?;
+            Property::Other
+        }
+        b'D' | b'L' => {
+            reader.take(8)?;
+            Property::Other
+        }
+        b'i' => Property::IntArray(
+            read_array(reader, 4)?
+                .chunks_exact(4)
+                .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect(),
+        ),
+        b'f' => {
+            read_array(reader, 4)?;
+            Property::Other
+        }
+        b'd' | b'l' => {
+            read_array(reader, 8)?;
+            Property::Other
+        }
+        b'b' => {
+            read_array(reader, 1)?;
+            Property::Other
+        }
+        b'S' => {
+            let length = reader.u32()? as usize;
+            Property::String(String::from_utf8_lossy(reader.take(length)?).into_owned())
+        }
+        b'R' => {
+            let length = reader.u32()? as usize;
+            reader.take(length)?;
+            Property::Other
+        }
+        kind => bail!("FBX property has an unknown type {}", kind as char),
+    };
+
+    Ok(property)
+}
+
+/// Reads a node and its children, or nothing if this is the empty record that
+/// ends a list of nodes.
+fn read_node(reader: &mut Reader, wide: bool, depth: usize) -> anyhow::Result<Option<Node>> {
+    if depth > MAX_DEPTH {
+        bail!("FBX nodes are nested too deeply");
+    }
+
+    let (end_offset, property_count) = if wide {
+        let end_offset = reader.u64()?;
+        let property_count = reader.u64()?;
+        reader.u64()?;
+        (end_offset, property_count)
+    } else {
+        let end_offset = reader.u32()? as u64;
+        let property_count = reader.u32()? as u64;
+        reader.u32()?;
+        (end_offset, property_count)
+    };
+    let name_length = reader.u8()? as usize;
+
+    if end_offset == 0 {
+        return Ok(None);
+    }
+    let end_offset = usize::try_from(end_offset)?;
+
+    let name = String::from_utf8_lossy(reader.take(name_length)?).into_owned();
+    let mut properties = Vec::new();
+    for _ in 0..property_count {
+        properties.push(read_property(reader)?);
+    }
+
+    let mut children = Vec::new();
+    while reader.position < end_offset {
+        match read_node(reader, wide, depth + 1)? {
+            Some(child) => children.push(child),
+            None => break,
+        }
+    }
+    if reader.position != end_offset {
+        bail!("FBX node {name} has the wrong length");
+    }
+
+    Ok(Some(Node {
+        name,
+        properties,
+        children,
+    }))
+}
+
+/// The name of a geometry node, which FBX stores as `name\0\x01class`.
+fn geometry_name(node: &Node) -> String {
+    node.properties
+        .iter()
+        .find_map(|property| match property {
+            Property::String(name) => Some(name.split('\0').next().unwrap_or_default()),
+            _ => None,
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or("unnamed mesh")
+        .to_string()
+}
+
+/// Counts the triangles of a polygon list, where the last index of each
+/// polygon is stored negated, minus one.
+fn count_triangles(indices: &[i32]) -> u64 {
+    let mut triangles = 0;
+    let mut vertices: u64 = 0;
+
+    for index in indices {
+        vertices += 1;
+        if *index < 0 {
+            triangles += vertices.saturating_sub(2);
+            vertices = 0;
+        }
+    }
+
+    triangles
+}
+
+fn find_meshes(node: &Node, meshes: &mut Vec<(String, u64)>) {
+    if node.name == "Geometry" {
+        let indices = node
+            .children
+            .iter()
+            .filter(|child| child.name == "PolygonVertexIndex")
+            .flat_map(|child| &child.properties)
+            .find_map(|property| match property {
+                Property::IntArray(indices) => Some(indices),
+                _ => None,
+            });
+        if let Some(indices) = indices {
+            meshes.push((geometry_name(node), count_triangles(indices)));
+        }
+    }
+
+    for child in &node.children {
+        find_meshes(child, meshes);
+    }
+}
+
+/// The name and triangle count of every mesh in a binary FBX file.
+fn read_meshes(data: &[u8]) -> anyhow::Result<Vec<(String, u64)>> {
+    let mut reader = Reader {
+        data,
+        position: BINARY_MAGIC.len() + 2,
+    };
+    let version = reader.u32()?;
+    let wide = version >= 7500;
+
+    let mut meshes = Vec::new();
+    while let Some(node) = read_node(&mut reader, wide, 0)? {
+        find_meshes(&node, &mut meshes);
+    }
+
+    Ok(meshes)
+}
+
+/// Fails if a binary FBX file has a mesh with more triangles than Roblox
+/// accepts. Text FBX files aren't checked, and files that can't be read are
+/// left for Roblox to judge, returning a warning saying so.
+pub fn validate_fbx(name: &str, data: &[u8]) -> anyhow::Result<Option<String>> {
+    if !data.starts_with(BINARY_MAGIC) {
+        return Ok(None);
+    }
+
+    let meshes = match read_meshes(data) {
+        Ok(meshes) => meshes,
+        Err(error) => {
+            return Ok(Some(format!(
+                "Couldn't count the triangles in {name}: {error:#}"
+            )))
+        }
+    };
+
+    let too_large: Vec<_> = meshes
+        .iter()
+        .filter(|(_, triangles)| *triangles > MAX_TRIANGLES)
+        .map(|(name, triangles)| format!("{name} has {triangles}"))
+        .collect();
+    if !too_large.is_empty() {
+        bail!(
+            "Roblox doesn't accept meshes with more than {MAX_TRIANGLES} triangles, but {}",
+            too_large.join(", ")
+        );
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_triangles, read_meshes, validate_fbx, BINARY_MAGIC};
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    /// Writes binary FBX files, with either header width.
+    struct Writer {
+        data: Vec<u8>,
+        wide: bool,
+    }
+
+    impl Writer {
+        fn new(wide: bool) -> Self {
+            let mut data = BINARY_MAGIC.to_vec();
+            data.extend([0x1a, 0]);
+            data.extend(if wide { 7500u32 } else { 7400u32 }.to_le_bytes());
+            Self { data, wide }
+        }
+
+        fn header_value(&mut self, value: usize) {
+            if self.wide {
+                self.data.extend((value as u64).to_le_bytes());
+            } else {
+                self.data.extend((value as u32).to_le_bytes());
+            }
+        }
+
+        fn node(&mut self, name: &str, properties: &[Vec<u8>], children: impl FnOnce(&mut Self)) {
+            let start = self.data.len();
+            self.header_value(0);
+            self.header_value(properties.len());
+            self.header_value(properties.iter().map(Vec::len).sum());
+            self.data.push(name.len() as u8);
+            self.data.extend(name.as_bytes());
+            for property in properties {
+                self.data.extend(property);
+            }
+
+            let children_start = self.data.len();
+            children(self);
+            if self.data.len() != children_start {
+                self.end();
+            }
+
+            let end = self.data.len();
+            if self.wide {
+                self.data[start..start + 8].copy_from_slice(&(end as u64).to_le_bytes());
+            } else {
+                self.data[start..start + 4].copy_from_slice(&(end as u32).to_le_bytes());
+            }
+        }
+
+        /// The empty record that ends a list of nodes.
+        fn end(&mut self) {
+            let size = if self.wide { 25 } else { 13 };
+            self.data.extend(vec![0; size]);
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            self.end();
+            self.data
+        }
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut property = vec![b'S'];
+        property.extend((value.len() as u32).to_le_bytes());
+        property.extend(value.as_bytes());
+        property
+    }
+
+    fn int_array(values: &[i32], compressed: bool) -> Vec<u8> {
+        let mut bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        if compressed {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&bytes).unwrap();
+            bytes = encoder.finish().unwrap();
+        }
+
+        let mut property = vec![b'i'];
+        property.extend((values.len() as u32).to_le_bytes());
+        property.extend((compressed as u32).to_le_bytes());
+        property.extend((bytes.len() as u32).to_le_bytes());
+        property.extend(bytes);
+        property
+    }
+
+    /// A file with a quad and a triangle in one mesh.
+    fn mesh_file(wide: bool, compressed: bool) -> Vec<u8> {
+        let mut writer = Writer::new(wide);
+        writer.node("FBXHeaderExtension", &[], |_| {});
+        writer.node("Objects", &[], |writer| {
+            writer.node("Geometry", &[string("Cube\0\x01Geometry")], |writer| {
+                writer.node(
+                    "PolygonVertexIndex",
+                    &[int_array(&[0, 1, 2, -4, 4, 5, -7], compressed)],
+                    |_| {},
+                );
+            });
+        });
+        writer.finish()
+    }
+
+    #[test]
+    fn counts_triangles() {
+        assert_eq!(count_triangles(&[]), 0);
+        assert_eq!(count_triangles(&[0, 1, -3]), 1);
+        assert_eq!(count_triangles(&[0, 1, 2, -4, 4, 5, -7]), 3);
+        assert_eq!(count_triangles(&[0, 1, 2, 3, -5]), 3);
+    }
+
+    #[test]
+    fn reads_meshes() {
+        for wide in [false, true] {
+            for compressed in [false, true] {
+                assert_eq!(
+                    read_meshes(&mesh_file(wide, compressed)).unwrap(),
+                    vec![("Cube".to_string(), 3)]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_array_lengths_past_the_data() {
+        let mut property = int_array(&[0, 1, -3], true);
+        property[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut writer = Writer::new(false);
+        writer.node("Geometry", &[string("Huge")], |writer| {
+            writer.node("PolygonVertexIndex", &[property], |_| {});
+        });
+
+        let error = read_meshes(&writer.finish()).unwrap_err();
+        assert_eq!(error.to_string(), "FBX array is the wrong length");
+    }
+
+    #[test]
+    fn rejects_deeply_nested_nodes() {
+        fn nest(writer: &mut Writer, depth: usize) {
+            if depth > 0 {
+                writer.node("Node", &[], |writer| nest(writer, depth - 1));
+            }
+        }
+
+        let mut writer = Writer::new(false);
+        nest(&mut writer, 100);
+
+        let error = read_meshes(&writer.finish()).unwrap_err();
+        assert_eq!(error.to_string(), "FBX nodes are nested too deeply");
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let data = mesh_file(false, false);
+
+        assert!(read_meshes(&data[..data.len() - 20]).is_err());
+    }
+
+    #[test]
+    fn warns_about_unreadable_files() {
+        let data = mesh_file(false, false);
+        assert_eq!(validate_fbx("cube.fbx", &data).unwrap(), None);
+
+        let warning = validate_fbx("cube.fbx", &data[..data.len() - 20]).unwrap();
+        assert!(warning
+            .unwrap()
+            .starts_with("Couldn't count the triangles in cube.fbx"));
+    }
+}
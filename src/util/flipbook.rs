@@ -0,0 +1,171 @@
+//! Lays the frames of an animated image out in a single sprite sheet, since
+//! Roblox only accepts still images, so it can be played back as a flipbook.
+
+use anyhow::{bail, Context};
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder},
+    imageops, AnimationDecoder, Frame, ImageFormat, RgbaImage,
+};
+use std::io::Cursor;
+
+/// Roblox downscales images wider or taller than this, which would blur
+/// every frame.
+pub const MAX_SHEET_SIZE: u32 = 1024;
+
+/// Where each frame of an animated image is in the sheet it is uploaded as.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flipbook {
+    pub frame_width: u32,
+    pub frame_height: u32,
+    /// How many frames are in each row, left to right.
+    pub columns: u32,
+    /// How long each frame is shown, in milliseconds.
+    pub durations: Vec<u32>,
+}
+
+impl Flipbook {
+    /// The width and height of the sheet the frames are laid out in.
+    pub fn sheet_size(&self) -> (u32, u32) {
+        let rows = (self.durations.len() as u32).div_ceil(self.columns.max(1));
+        (self.frame_width * self.columns, self.frame_height * rows)
+    }
+}
+
+fn decode_frames(data: &[u8], ext: &str) -> anyhow::Result<Vec<Frame>> {
+    let frames = match ext {
+        "gif" => GifDecoder::new(Cursor::new(data))?.into_frames(),
+        "apng" => PngDecoder::new(Cursor::new(data))?.apng()?.into_frames(),
+        _ => unreachable!("{ext} is not an animated format"),
+    };
+
+    Ok(frames.collect_frames()?)
+}
+
+/// Renders an animated GIF or APNG to a PNG of its frames in order, left to
+/// right and then top to bottom.
+pub fn animation_to_sheet(data: &[u8], ext: &str) -> anyhow::Result<(Vec<u8>, Flipbook)> {
+    let frames = decode_frames(data, ext).context("Failed to decode animation")?;
+    if frames.is_empty() {
+        bail!("Animation has no frames");
+    }
+
+    let frame_width = frames
+        .iter()
+        .map(|frame| frame.left() + frame.buffer().width())
+        .max()
+        .unwrap_or_default();
+    let frame_height = frames
+        .iter()
+        .map(|frame| frame.top() + frame.buffer().height())
+        .max()
+        .unwrap_or_default();
+
+    let count = frames.len() as u32;
+    let columns = (MAX_SHEET_SIZE / frame_width.max(1)).clamp(1, count);
+    let rows = count.div_ceil(columns);
+
+    let mut sheet = RgbaImage::new(frame_width * columns, frame_height * rows);
+    let mut durations = Vec::new();
+    for (index, frame) in frames.iter().enumerate() {
+        let index = index as u32;
+        let x = (index % columns) * frame_width + frame.left();
+        let y = (index / columns) * frame_height + frame.top();
+        imageops::replace(&mut sheet, frame.buffer(), x as i64, y as i64);
+
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        durations.push(numerator / denominator.max(1));
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    sheet
+        .write_to(&mut png, ImageFormat::Png)
+        .context("Failed to encode PNG")?;
+
+    Ok((
+        png.into_inner(),
+        Flipbook {
+            frame_width,
+            frame_height,
+            columns,
+            durations,
+        },
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use image::{codecs::gif::GifEncoder, Delay, Rgba};
+
+    /// A GIF of solid frames, each given as its width, height, colour and
+    /// delay in milliseconds.
+    pub(crate) fn gif(frames: &[(u32, u32, [u8; 4], u32)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut data);
+            for &(width, height, color, delay) in frames {
+                let buffer = RgbaImage::from_pixel(width, height, Rgba(color));
+                let delay = Delay::from_numer_denom_ms(delay, 1);
+                encoder
+                    .encode_frame(Frame::from_parts(buffer, 0, 0, delay))
+                    .unwrap();
+            }
+        }
+        data
+    }
+
+    fn sheet(frames: &[(u32, u32, [u8; 4], u32)]) -> (RgbaImage, Flipbook) {
+        let (png, flipbook) = animation_to_sheet(&gif(frames), "gif").unwrap();
+        let sheet = image::load_from_memory_with_format(&png, ImageFormat::Png)
+            .unwrap()
+            .to_rgba8();
+        (sheet, flipbook)
+    }
+
+    #[test]
+    fn lays_frames_out_in_a_row() {
+        let (sheet, flipbook) = sheet(&[
+            (4, 2, [255, 0, 0, 255], 100),
+            (4, 2, [0, 255, 0, 255], 50),
+            (4, 2, [0, 0, 255, 255], 200),
+        ]);
+
+        assert_eq!(
+            flipbook,
+            Flipbook {
+                frame_width: 4,
+                frame_height: 2,
+                columns: 3,
+                durations: vec![100, 50, 200],
+            }
+        );
+        assert_eq!(sheet.dimensions(), (12, 2));
+        assert_eq!(sheet.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(sheet.get_pixel(4, 1).0, [0, 255, 0, 255]);
+        assert_eq!(sheet.get_pixel(11, 1).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn wraps_rows_at_the_sheet_limit() {
+        let (sheet, flipbook) = sheet(&[(400, 1, [255, 255, 255, 255], 100); 5]);
+
+        assert_eq!(flipbook.columns, MAX_SHEET_SIZE / 400);
+        assert_eq!(flipbook.sheet_size(), (800, 3));
+        assert_eq!(sheet.dimensions(), flipbook.sheet_size());
+    }
+
+    #[test]
+    fn reports_sheets_past_the_limit() {
+        let tall = Flipbook {
+            frame_width: 600,
+            frame_height: 300,
+            columns: 1,
+            durations: vec![100; 4],
+        };
+        assert_eq!(tall.sheet_size(), (600, 1200));
+
+        let (_, wide) = sheet(&[(1050, 1, [255, 255, 255, 255], 100); 2]);
+        assert_eq!(wide.columns, 1);
+        assert_eq!(wide.sheet_size(), (1050, 2));
+    }
+}
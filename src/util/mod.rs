@@ -1,2 +1,9 @@
 pub mod alpha_bleed;
+pub mod audio;
+pub mod color_profile;
+pub mod fbx;
+pub mod flipbook;
+pub mod optimize_png;
+pub mod path;
+pub mod premultiply;
 pub mod svg;
@@ -0,0 +1,153 @@
+//! Shrinks PNGs before they are uploaded, so they download faster in game.
+//! Images with few colors, like most UI, are much smaller with a palette.
+
+use anyhow::Context;
+use color_quant::NeuQuant;
+use image::{
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+    ExtendedColorType, ImageEncoder, RgbaImage,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PngOptimization {
+    /// Recompresses images, using a palette when they have few enough colors
+    /// that nothing changes.
+    Lossless,
+    /// Also reduces images with more colors to a palette, which may change
+    /// gradients and soft edges.
+    Lossy,
+}
+
+/// The most colors a PNG palette can hold.
+const PALETTE_SIZE: usize = 256;
+
+/// How many pixels NeuQuant skips between samples, trading quality for speed.
+const QUANTIZE_SAMPLE_FACTOR: i32 = 10;
+
+/// An image as indices into a palette of RGBA colors.
+struct Indexed {
+    palette: Vec<[u8; 4]>,
+    indices: Vec<u8>,
+}
+
+/// The image's exact palette, if it has few enough colors for one.
+fn exact_palette(image: &RgbaImage) -> Option<Indexed> {
+    let mut lookup = HashMap::new();
+    let mut palette = Vec::new();
+    let mut indices = Vec::with_capacity(image.as_raw().len() / 4);
+
+    for pixel in image.pixels() {
+        let index = match lookup.get(&pixel.0) {
+            Some(index) => *index,
+            None => {
+                if palette.len() == PALETTE_SIZE {
+                    return None;
+                }
+                let index = palette.len() as u8;
+                lookup.insert(pixel.0, index);
+                palette.push(pixel.0);
+                index
+            }
+        };
+        indices.push(index);
+    }
+
+    Some(Indexed { palette, indices })
+}
+
+/// The closest palette NeuQuant can find for the image.
+fn quantized_palette(image: &RgbaImage) -> Indexed {
+    let quantizer = NeuQuant::new(QUANTIZE_SAMPLE_FACTOR, PALETTE_SIZE, image.as_raw());
+
+    Indexed {
+        palette: quantizer
+            .color_map_rgba()
+            .chunks_exact(4)
+            .map(|color| [color[0], color[1], color[2], color[3]])
+            .collect(),
+        indices: image
+            .pixels()
+            .map(|pixel| quantizer.index_of(&pixel.0) as u8)
+            .collect(),
+    }
+}
+
+fn encode_truecolor(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let encoder =
+        PngEncoder::new_with_quality(&mut data, CompressionType::Best, FilterType::Adaptive);
+
+    // Opaque images don't need to store alpha at all.
+    if image.pixels().all(|pixel| pixel[3] == u8::MAX) {
+        let rgb: Vec<u8> = image
+            .pixels()
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]])
+            .collect();
+        encoder.write_image(&rgb, image.width(), image.height(), ExtendedColorType::Rgb8)?;
+    } else {
+        encoder.write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ExtendedColorType::Rgba8,
+        )?;
+    }
+
+    Ok(data)
+}
+
+fn encode_indexed(image: &RgbaImage, indexed: &Indexed) -> anyhow::Result<Vec<u8>> {
+    let rgb: Vec<u8> = indexed
+        .palette
+        .iter()
+        .flat_map(|color| [color[0], color[1], color[2]])
+        .collect();
+    let alpha: Vec<u8> = indexed.palette.iter().map(|color| color[3]).collect();
+
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, image.width(), image.height());
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(rgb);
+    if alpha.iter().any(|alpha| *alpha != u8::MAX) {
+        encoder.set_trns(alpha);
+    }
+    encoder.set_compression(png::Compression::Best);
+    // Filtering rarely helps palette images, as neighboring indices aren't
+    // related the way neighboring colors are.
+    encoder.set_filter(png::FilterType::NoFilter);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&indexed.indices)?;
+    writer.finish()?;
+
+    Ok(data)
+}
+
+/// Re-encodes a PNG in whichever way is smallest, returning it unchanged if
+/// nothing is smaller.
+pub fn optimize_png(data: Vec<u8>, optimization: PngOptimization) -> anyhow::Result<Vec<u8>> {
+    let image = image::load_from_memory(&data)
+        .context("Failed to decode PNG")?
+        .into_rgba8();
+
+    let mut candidates = vec![encode_truecolor(&image)?];
+
+    let indexed = match exact_palette(&image) {
+        Some(indexed) => Some(indexed),
+        None if optimization == PngOptimization::Lossy => Some(quantized_palette(&image)),
+        None => None,
+    };
+    if let Some(indexed) = indexed {
+        candidates.push(encode_indexed(&image, &indexed)?);
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|candidate| candidate.len() < data.len())
+        .min_by_key(|candidate| candidate.len())
+        .unwrap_or(data))
+}
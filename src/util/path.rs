@@ -0,0 +1,81 @@
+//! Compares paths from the config, which can name the same place in more
+//! than one way, like `assets` and `./assets`.
+
+use std::path::{absolute, Component, Path, PathBuf};
+
+/// Makes a path absolute and removes its `.` and `..` components, without
+/// touching the file system, so it works for paths that don't exist yet.
+pub fn normalize(path: &Path) -> PathBuf {
+    let path = if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    };
+    let absolute = absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// The path to `path` from the directory `base`, like `../src/assets.luau`.
+pub fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path = normalize(path);
+    let base = normalize(base);
+
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in base.components().skip(common) {
+        relative.push("..");
+    }
+    for component in path.components().skip(common) {
+        relative.push(component);
+    }
+
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::current_dir;
+
+    #[test]
+    fn normalizes_paths() {
+        let cwd = current_dir().unwrap();
+
+        assert_eq!(normalize(Path::new("assets")), cwd.join("assets"));
+        assert_eq!(normalize(Path::new("./assets/")), cwd.join("assets"));
+        assert_eq!(
+            normalize(Path::new("src/../assets/./ui")),
+            cwd.join("assets/ui")
+        );
+        assert_eq!(normalize(Path::new("")), cwd);
+    }
+
+    #[test]
+    fn finds_relative_paths() {
+        assert_eq!(
+            relative_to(Path::new("src/a.luau"), Path::new("")),
+            Path::new("src/a.luau")
+        );
+        assert_eq!(
+            relative_to(Path::new("./src/a.luau"), Path::new("build/rojo")),
+            Path::new("../../src/a.luau")
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Multiplies the colour of each pixel by its alpha, for images that are
+//! composited as if they already were.
+
+use image::DynamicImage;
+
+pub(crate) fn premultiply_alpha(img: &mut DynamicImage) {
+    if !img.color().has_alpha() {
+        return;
+    }
+
+    if let DynamicImage::ImageRgba16(rgba) = img {
+        for pixel in rgba.pixels_mut() {
+            let alpha = pixel[3] as u32;
+            for channel in &mut pixel.0[..3] {
+                *channel = ((*channel as u32 * alpha + 32767) / 65535) as u16;
+            }
+        }
+        return;
+    }
+
+    let mut rgba = img.to_rgba8();
+    for pixel in rgba.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel.0[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+    *img = DynamicImage::ImageRgba8(rgba);
+}
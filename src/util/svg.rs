@@ -1,12 +1,69 @@
 use std::sync::Arc;
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use resvg::{
     tiny_skia::Pixmap,
     usvg::{fontdb::Database, Options, Transform, Tree},
 };
 
-pub async fn svg_to_png(data: &[u8], fontdb: Arc<Database>) -> anyhow::Result<Vec<u8>> {
+/// DejaVu Sans, used when no system font can render a generic family, so SVG
+/// text renders the same in containers without any fonts installed.
+#[cfg(feature = "embedded-font")]
+static EMBEDDED_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+
+/// Loads the fonts SVG text is rendered with.
+pub fn font_database() -> Database {
+    let mut font_db = Database::new();
+    font_db.load_system_fonts();
+
+    #[cfg(feature = "embedded-font")]
+    add_embedded_font(&mut font_db);
+
+    font_db
+}
+
+/// Loads the embedded font, and uses it for any generic family that no
+/// system font provides. Text that names a missing font falls back to the
+/// serif family, so this covers every SVG.
+#[cfg(feature = "embedded-font")]
+fn add_embedded_font(font_db: &mut Database) {
+    use resvg::usvg::fontdb::{Family, Query};
+
+    let missing = |font_db: &Database, family: Family| {
+        font_db
+            .query(&Query {
+                families: &[family],
+                ..Default::default()
+            })
+            .is_none()
+    };
+
+    font_db.load_font_data(EMBEDDED_FONT.to_vec());
+    let name = "DejaVu Sans";
+
+    if missing(font_db, Family::Serif) {
+        font_db.set_serif_family(name);
+    }
+    if missing(font_db, Family::SansSerif) {
+        font_db.set_sans_serif_family(name);
+    }
+    if missing(font_db, Family::Monospace) {
+        font_db.set_monospace_family(name);
+    }
+    if missing(font_db, Family::Cursive) {
+        font_db.set_cursive_family(name);
+    }
+    if missing(font_db, Family::Fantasy) {
+        font_db.set_fantasy_family(name);
+    }
+}
+
+/// Renders an SVG to a PNG, at `scale` times its intrinsic size.
+pub async fn svg_to_png(data: &[u8], fontdb: Arc<Database>, scale: f32) -> anyhow::Result<Vec<u8>> {
+    if !scale.is_finite() || scale <= 0.0 {
+        bail!("SVG scale must be a positive number, not {scale}");
+    }
+
     let opt = Options {
         fontdb,
         ..Default::default()
@@ -15,9 +72,16 @@ pub async fn svg_to_png(data: &[u8], fontdb: Arc<Database>) -> anyhow::Result<Ve
     let rtree = Tree::from_data(data, &opt).context("Failed to parse SVG file")?;
     let pixmap_size = rtree.size();
 
-    let mut pixmap = Pixmap::new(pixmap_size.width() as u32, pixmap_size.height() as u32)
-        .context("Failed to create pixmap")?;
-    resvg::render(&rtree, Transform::identity(), &mut pixmap.as_mut());
+    let mut pixmap = Pixmap::new(
+        (pixmap_size.width() * scale).ceil() as u32,
+        (pixmap_size.height() * scale).ceil() as u32,
+    )
+    .context("Failed to create pixmap")?;
+    resvg::render(
+        &rtree,
+        Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
 
     let encoded = pixmap.encode_png().context("Failed to encode PNG")?;
 